@@ -0,0 +1,187 @@
+//! Throughput of `print()` filling a 20x4 screen.
+//!
+//! The `null` benchmark measures the driver alone and runs anywhere. The `gpio` benchmarks need a GPIO
+//! chip with 6 free lines; they compare `GpioBus`, which sets all data lines with a single call,
+//! with a bus setting each line separately. Delays are disabled so that only the cost of driving
+//! the lines is measured; the display does not need to be connected.
+//!
+//! The chip and the line offsets are read from the environment:
+//!
+//! - `RPI_LCD_BENCH_CHIP`: chip path, `/dev/gpiochip0` by default
+//! - `RPI_LCD_BENCH_RS`: RS line, 26 by default
+//! - `RPI_LCD_BENCH_ENABLE`: ENABLE line, 19 by default
+//! - `RPI_LCD_BENCH_DATA`: d4 to d7 lines separated by commas, `13,6,5,11` by default
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use gpio_cdev::{Chip, LineHandle, LineRequestFlags};
+use rpi_lcd::mock::NoDelay;
+use rpi_lcd::{Bus, CharSize, Error, GpioBus, GpioChip, GpioLine, LinePins, Mode, Register, LCD};
+use std::convert::TryFrom;
+use std::env;
+
+/// GPIO lines the benchmarks drive
+struct Lines {
+    chip: String,
+    rs: u32,
+    enable: u32,
+    data: [u32; 4],
+}
+
+impl Lines {
+    /// Reads the lines from the environment, falling back to the ones of the README example
+    fn from_env() -> Result<Lines, String> {
+        let var = |name: &str, default: &str| env::var(name).unwrap_or_else(|_| default.to_string());
+        let offset = |name: &str, value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| format!("{}: invalid line offset {:?}: {}", name, value, e))
+        };
+
+        let data = var("RPI_LCD_BENCH_DATA", "13,6,5,11")
+            .split(',')
+            .map(|value| offset("RPI_LCD_BENCH_DATA", value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = <[u32; 4]>::try_from(data)
+            .map_err(|_| "RPI_LCD_BENCH_DATA: expected 4 lines (d4 to d7)".to_string())?;
+
+        Ok(Lines {
+            chip: var("RPI_LCD_BENCH_CHIP", "/dev/gpiochip0"),
+            rs: offset("RPI_LCD_BENCH_RS", &var("RPI_LCD_BENCH_RS", "26"))?,
+            enable: offset("RPI_LCD_BENCH_ENABLE", &var("RPI_LCD_BENCH_ENABLE", "19"))?,
+            data,
+        })
+    }
+}
+
+const SCREEN: [&str; 4] = [
+    "The quick brown fox ",
+    "jumps over the lazy ",
+    "dog. THE QUICK BROWN",
+    "FOX JUMPS OVER THE L",
+];
+
+/// Bus discarding everything
+struct NullBus;
+
+impl Bus for NullBus {
+    fn mode(&self) -> Mode {
+        Mode::Bits4
+    }
+
+    fn set_rs(&mut self, _register: Register) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn set_enable(&mut self, _high: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_nibble(&mut self, _nibble: u8) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// GPIO bus setting each line with a separate call
+struct PerLineBus {
+    rs: LineHandle,
+    enable: LineHandle,
+    data: Vec<LineHandle>,
+}
+
+impl PerLineBus {
+    fn new(chip: &mut Chip, lines: &Lines) -> Result<PerLineBus, Error> {
+        let mut line = |offset| chip.get_line(offset)?.request(LineRequestFlags::OUTPUT, 0, "lcd");
+        let data = lines.data.iter().map(|offset| line(*offset)).collect::<Result<_, _>>()?;
+
+        Ok(PerLineBus {
+            rs: line(lines.rs)?,
+            enable: line(lines.enable)?,
+            data,
+        })
+    }
+}
+
+impl Bus for PerLineBus {
+    fn mode(&self) -> Mode {
+        Mode::Bits4
+    }
+
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        Ok(self.rs.set_value((register == Register::Data) as u8)?)
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        Ok(self.enable.set_value(high as u8)?)
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        for (i, line) in self.data.iter().enumerate() {
+            line.set_value((nibble >> i) & 0x01)?;
+        }
+        Ok(())
+    }
+
+    fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+        Err(Error::Unsupported("8-bit mode"))
+    }
+}
+
+fn print_screen<B: Bus>(lcd: &mut LCD<B, NoDelay>) {
+    for (row, text) in SCREEN.iter().enumerate() {
+        lcd.set_cursor(0, row as u8).unwrap();
+        lcd.print(text).unwrap();
+    }
+}
+
+fn bench_lcd<B: Bus>(c: &mut Criterion, name: &str, bus: B) {
+    let mut lcd = LCD::with_bus_and_delay(bus, NoDelay);
+    lcd.begin(20, 4, CharSize::Dots5x8).unwrap();
+    c.bench_function(name, |b| b.iter(|| print_screen(&mut lcd)));
+}
+
+fn print_full_screen(c: &mut Criterion) {
+    bench_lcd(c, "null print 20x4", NullBus);
+
+    let lines = match Lines::from_env() {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("skipping GPIO benchmarks, {}", e);
+            return;
+        }
+    };
+    let mut gpio_chip = match Chip::new(&lines.chip) {
+        Ok(gpio_chip) => gpio_chip,
+        Err(e) => {
+            eprintln!("skipping GPIO benchmarks, cannot open {}: {}", lines.chip, e);
+            return;
+        }
+    };
+
+    // each bus is dropped before the next one requests the same lines
+    match PerLineBus::new(&mut gpio_chip, &lines) {
+        Ok(bus) => bench_lcd(c, "gpio per-line print 20x4", bus),
+        Err(e) => eprintln!("skipping GPIO per-line benchmark, cannot request lines: {}", e),
+    }
+
+    let [d4, d5, d6, d7] = lines.data;
+    let bus = GpioBus::with_line_pins(LinePins {
+        chip: GpioChip::Path(lines.chip.into()),
+        rs: GpioLine::Offset(lines.rs),
+        rw: None,
+        enable: GpioLine::Offset(lines.enable),
+        data: [None, None, None, None, Some(d4.into()), Some(d5.into()), Some(d6.into()),
+            Some(d7.into())],
+    });
+    match bus {
+        Ok(bus) => bench_lcd(c, "gpio multi-line print 20x4", bus),
+        Err(e) => eprintln!("skipping GPIO multi-line benchmark, cannot request lines: {}", e),
+    }
+}
+
+criterion_group!(benches, print_full_screen);
+criterion_main!(benches);
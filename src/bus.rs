@@ -0,0 +1,74 @@
+use crate::{Error, Mode};
+
+/// LCD register selected by the RS line
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Register {
+    /// Instruction register (RS low); used to send commands
+    Instruction,
+
+    /// Data register (RS high); used to read and write DDRAM or CGRAM
+    Data,
+}
+
+/// Interface through which [LCD](struct.LCD.html) talks to the HD44780 controller
+///
+/// The trait abstracts the way the LCD lines are wired, for instance directly to GPIO pins, through
+/// an I2C expander or a shift register. Implementations only drive the lines; the timing of the
+/// protocol (enable pulse width, instruction execution time) is handled by `LCD`.
+///
+/// The read methods are optional. Implement them only if the RW line is wired and the bus is able
+/// to switch the data lines to inputs.
+pub trait Bus {
+    /// Returns the data bus width the display is wired with.
+    fn mode(&self) -> Mode;
+
+    /// Selects the register the next transfer goes to.
+    fn set_rs(&mut self, register: Register) -> Result<(), Error>;
+
+    /// Drives the ENABLE line. The display latches the data lines on the falling edge.
+    fn set_enable(&mut self, high: bool) -> Result<(), Error>;
+
+    /// Puts the 4 least significant bits of `nibble` on data lines d4 to d7.
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error>;
+
+    /// Puts `byte` on data lines d0 to d7.
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error>;
+
+    /// Returns `true` if the bus is able to read from the display.
+    fn can_read(&self) -> bool {
+        false
+    }
+
+    /// Drives the RW line: `true` to read from the display, `false` to write to it. Reading
+    /// requires the data lines to be switched to inputs.
+    fn set_rw(&mut self, _read: bool) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Samples the data lines. In 4-bit mode the value of d4 to d7 is returned in the 4 least
+    /// significant bits. Fails with `Error::Unsupported` if the bus cannot read from the display.
+    fn read_data(&mut self) -> Result<u8, Error> {
+        Err(Error::Unsupported("read"))
+    }
+
+    /// Returns the number of controllers the bus has an ENABLE line for, 2 for the 40x4
+    /// displays made of two HD44780 controllers sharing the RS, RW and data lines.
+    fn controllers(&self) -> u8 {
+        1
+    }
+
+    /// Selects the controller whose ENABLE line is driven by `set_enable()`, 0 being the first.
+    /// Fails with `Error::Unsupported` if the bus has no ENABLE line for `index`.
+    fn select_controller(&mut self, index: u8) -> Result<(), Error> {
+        match index {
+            0 => Ok(()),
+            _ => Err(Error::Unsupported("second controller")),
+        }
+    }
+
+    /// Switches the backlight on or off. Fails with `Error::Unsupported` if the bus does not
+    /// control the backlight.
+    fn set_backlight(&mut self, _on: bool) -> Result<(), Error> {
+        Err(Error::Unsupported("backlight"))
+    }
+}
@@ -0,0 +1,100 @@
+use crate::glyphs::Glyph;
+
+/// Number of custom characters of 5x8 dots
+pub(crate) const SLOTS: usize = 8;
+
+/// Allocation of the custom character slots to the glyphs synthesised by
+/// [print()](../struct.LCD.html#method.print)
+///
+/// Slots written by the application through `create_char()` are reserved and never reused.
+/// The other ones are handed out least recently used first. The 5x10 dots font has 4 slots of
+/// 16 bytes instead of 8 slots of 8 bytes.
+pub(crate) struct Cgram {
+    patterns: [Option<Glyph>; SLOTS],
+    slots: u8,
+    reserved: u8,
+    last_used: [u32; SLOTS],
+    clock: u32,
+}
+
+impl Cgram {
+
+    /// Creates the allocation of `slots` custom characters, 8 or 4
+    pub(crate) fn new(slots: u8) -> Cgram {
+        Cgram {
+            patterns: [None; SLOTS],
+            slots,
+            reserved: 0,
+            last_used: [0; SLOTS],
+            clock: 0,
+        }
+    }
+
+    /// Returns the number of custom characters
+    pub(crate) fn slots(&self) -> u8 {
+        self.slots
+    }
+
+    /// Returns the CGRAM address of `slot`
+    pub(crate) fn address(&self, slot: u8) -> u8 {
+        slot * (64 / self.slots)
+    }
+
+    /// Returns the character code showing `slot`
+    pub(crate) fn code(&self, slot: u8) -> u8 {
+        if self.slots == SLOTS as u8 {
+            slot
+        } else {
+            slot << 1
+        }
+    }
+
+    /// Returns the slot character `code` shows, if it is a custom character
+    pub(crate) fn slot(&self, code: u8) -> Option<u8> {
+        match code {
+            0x00..=0x0f if self.slots == SLOTS as u8 => Some(code & 0x07),
+            0x00..=0x0f => Some((code >> 1) & 0x03),
+            _ => None,
+        }
+    }
+
+    /// Records that the application wrote `pattern` to `slot`
+    pub(crate) fn reserve(&mut self, slot: u8, pattern: Glyph) {
+        self.patterns[slot as usize] = Some(pattern);
+        self.reserved |= 1 << slot;
+    }
+
+    /// Returns the slot holding `pattern` and marks it as used
+    pub(crate) fn find(&mut self, pattern: &Glyph) -> Option<u8> {
+        let slot = self.patterns.iter().position(|p| p.as_ref() == Some(pattern))?;
+        self.touch(slot);
+        Some(slot as u8)
+    }
+
+    /// Returns the least recently used slot that is neither reserved nor `in_use`, empty slots
+    /// first
+    pub(crate) fn victim(&self, in_use: impl Fn(u8) -> bool) -> Option<u8> {
+        (0..self.slots)
+            .filter(|slot| self.reserved & (1 << slot) == 0 && !in_use(*slot))
+            .min_by_key(|slot| match self.patterns[*slot as usize] {
+                None => (false, 0),
+                Some(_) => (true, self.last_used[*slot as usize]),
+            })
+    }
+
+    /// Records that `pattern` has been loaded into `slot` and marks it as used
+    pub(crate) fn load(&mut self, slot: u8, pattern: Glyph) {
+        self.patterns[slot as usize] = Some(pattern);
+        self.touch(slot as usize);
+    }
+
+    /// Returns the pattern loaded into `slot`
+    pub(crate) fn pattern(&self, slot: u8) -> Option<Glyph> {
+        self.patterns[slot as usize]
+    }
+
+    fn touch(&mut self, slot: usize) {
+        self.clock = self.clock.wrapping_add(1);
+        self.last_used[slot] = self.clock;
+    }
+}
@@ -0,0 +1,330 @@
+use core::slice;
+
+/// Character ROM of the controller
+///
+/// The ROM is given by the suffix of the controller part number, e.g. HD44780UA00 or
+/// HD44780UA02. Most displays sold today use the A00 ROM.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rom {
+    /// Japanese ROM: ASCII (with `¥`, `→` and `←` in place of `\`, `~` and DEL), half-width
+    /// katakana and a few Greek letters and symbols
+    A00,
+
+    /// European ROM: ASCII and the Latin-1 letters and symbols
+    A02,
+}
+
+impl Rom {
+
+    /// Returns the ROM code of `c`, or `None` if the ROM has no such character. Characters
+    /// `'\u{0}'` to `'\u{7}'` map to the custom characters.
+    pub fn code(&self, c: char) -> Option<u8> {
+        match c {
+            '\u{0}'..='\u{7}' => Some(c as u8),
+            _ => match self {
+                Rom::A00 => a00_code(c),
+                Rom::A02 => a02_code(c),
+            },
+        }
+    }
+}
+
+fn a00_code(c: char) -> Option<u8> {
+    Some(match c {
+        '\\' | '~' => return None,
+        ' '..='}' => c as u8,
+        '¥' => 0x5c,
+        '→' => 0x7e,
+        '←' => 0x7f,
+        // JIS X 0201 half-width katakana and punctuation
+        '\u{ff61}'..='\u{ff9f}' => (c as u32 - 0xff61 + 0xa1) as u8,
+        '。' => 0xa1,
+        '「' => 0xa2,
+        '」' => 0xa3,
+        '、' => 0xa4,
+        '・' => 0xa5,
+        'ー' => 0xb0,
+        '°' => 0xdf,
+        'α' => 0xe0,
+        'ä' => 0xe1,
+        'β' | 'ß' => 0xe2,
+        'ε' => 0xe3,
+        'μ' | 'µ' => 0xe4,
+        'σ' => 0xe5,
+        'ρ' => 0xe6,
+        '√' => 0xe8,
+        '¢' => 0xec,
+        '£' => 0xed,
+        'ñ' => 0xee,
+        'ö' => 0xef,
+        'θ' => 0xf2,
+        '∞' => 0xf3,
+        'Ω' => 0xf4,
+        'ü' => 0xf5,
+        'Σ' => 0xf6,
+        'π' => 0xf7,
+        '千' => 0xfa,
+        '万' => 0xfb,
+        '円' => 0xfc,
+        '÷' => 0xfd,
+        '█' => 0xff,
+        _ => return None,
+    })
+}
+
+fn a02_code(c: char) -> Option<u8> {
+    Some(match c {
+        ' '..='~' => c as u8,
+        '⌂' => 0x7f,
+        '\u{a0}' => b' ',
+        '¡'..='ÿ' => c as u8,
+        _ => return None,
+    })
+}
+
+/// Replacement of the characters the ROM does not have
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Fallback {
+    /// Replace the character with the given ROM code
+    Substitute(u8),
+
+    /// Replace the character with an ASCII approximation (e.g. `é` with `e`, `ß` with `ss` or
+    /// `€` with `EUR`), or with the given ROM code if there is none
+    Transliterate(u8),
+}
+
+/// Translation of text to the character codes of the controller ROM
+///
+/// # Examples
+///
+/// ```rust
+/// use rpi_lcd::{Charset, Fallback};
+///
+/// let charset = Charset::A00;
+/// assert!(charset.encode('°').eq([0xdf]));
+/// assert!(charset.encode('€').eq(*b"EUR"));
+///
+/// let charset = Charset {
+///     fallback: Fallback::Substitute(0xff),
+///     ..Charset::A02
+/// };
+/// assert!(charset.encode('é').eq([0xe9]));
+/// assert!(charset.encode('→').eq([0xff]));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Charset {
+    /// Character ROM of the controller
+    pub rom: Rom,
+
+    /// Replacement of the characters the ROM does not have
+    pub fallback: Fallback,
+}
+
+impl Charset {
+
+    /// A00 ROM, transliterating the characters it does not have or replacing them with `?`.
+    /// This is the default.
+    pub const A00: Charset = Charset {
+        rom: Rom::A00,
+        fallback: Fallback::Transliterate(b'?'),
+    };
+
+    /// A02 ROM, transliterating the characters it does not have or replacing them with `?`
+    pub const A02: Charset = Charset {
+        rom: Rom::A02,
+        fallback: Fallback::Transliterate(b'?'),
+    };
+
+    /// Returns the ROM codes `c` is shown with: a single code if the ROM has the character or
+    /// a substitute, several ones for a transliteration
+    pub fn encode(&self, c: char) -> Encoded {
+        let (code, transliteration, substitute) = match (self.rom.code(c), self.fallback) {
+            (Some(code), _) => (Some(code), "", 0),
+            (None, Fallback::Substitute(substitute)) => (Some(substitute), "", substitute),
+            (None, Fallback::Transliterate(substitute)) => match transliterate(c) {
+                Some(ascii) => (None, ascii, substitute),
+                None => (Some(substitute), "", substitute),
+            },
+        };
+
+        Encoded {
+            code,
+            transliteration: transliteration.as_bytes().iter(),
+            rom: self.rom,
+            substitute,
+        }
+    }
+
+    /// Returns the ROM code of the characters that cannot be shown otherwise
+    pub(crate) fn substitute(&self) -> u8 {
+        match self.fallback {
+            Fallback::Substitute(code) | Fallback::Transliterate(code) => code,
+        }
+    }
+}
+
+impl Default for Charset {
+    fn default() -> Self {
+        Charset::A00
+    }
+}
+
+/// Iterator over the ROM codes of a character, returned by
+/// [Charset::encode()](struct.Charset.html#method.encode)
+#[derive(Debug, Clone)]
+pub struct Encoded {
+    code: Option<u8>,
+    transliteration: slice::Iter<'static, u8>,
+    rom: Rom,
+    substitute: u8,
+}
+
+impl Encoded {
+
+    /// Returns the iterator over the single ROM code `code`
+    pub(crate) fn code(code: u8) -> Encoded {
+        Encoded {
+            code: Some(code),
+            transliteration: [].iter(),
+            rom: Rom::A00,
+            substitute: code,
+        }
+    }
+}
+
+impl Iterator for Encoded {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(code) = self.code.take() {
+            return Some(code);
+        }
+        self.transliteration
+            .next()
+            .map(|b| self.rom.code(*b as char).unwrap_or(self.substitute))
+    }
+}
+
+/// Returns the ASCII approximation of `c`
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'ç' | 'ć' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È'..='Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'Ì'..='Ï' | 'Ī' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ī' | 'į' | 'ı' => "i",
+        'Ł' => "L",
+        'ł' => "l",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ř' => "R",
+        'ř' => "r",
+        'Ś' | 'Š' | 'Ş' => "S",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'Ť' => "T",
+        'ť' => "t",
+        'Ù'..='Ü' | 'Ů' | 'Ű' | 'Ū' => "U",
+        'ù'..='ü' | 'ů' | 'ű' | 'ū' => "u",
+        'Ý' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        // Cyrillic letters looking like Latin ones
+        'А' => "A",
+        'В' => "B",
+        'Е' => "E",
+        'К' => "K",
+        'М' => "M",
+        'Н' => "H",
+        'О' => "O",
+        'Р' => "P",
+        'С' => "C",
+        'Т' => "T",
+        'Х' => "X",
+        'а' => "a",
+        'е' => "e",
+        'о' => "o",
+        'р' => "p",
+        'с' => "c",
+        'у' => "y",
+        'х' => "x",
+        '\u{a0}' => " ",
+        '‘' | '’' | '´' => "'",
+        '“' | '”' | '„' | '«' | '»' => "\"",
+        '–' | '—' | '−' => "-",
+        '…' => "...",
+        '•' | '·' => ".",
+        '×' => "x",
+        '÷' => "/",
+        '±' => "+-",
+        '°' => "o",
+        'µ' => "u",
+        '©' => "(c)",
+        '®' => "(R)",
+        '™' => "TM",
+        '€' => "EUR",
+        '£' => "GBP",
+        '¥' => "JPY",
+        '→' => "->",
+        '←' => "<-",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn codes(charset: Charset, text: &str) -> Vec<u8> {
+        text.chars().flat_map(|c| charset.encode(c)).collect()
+    }
+
+    #[test]
+    fn maps_characters_to_a00_codes() {
+        let charset = Charset::A00;
+        assert_eq!(codes(charset, "25°C"), b"25\xdfC");
+        assert_eq!(codes(charset, "µ ä ö ü ñ"), b"\xe4 \xe1 \xef \xf5 \xee");
+        assert_eq!(codes(charset, "→¥←"), b"\x7e\x5c\x7f");
+        assert_eq!(codes(charset, "ｱｲｳ"), b"\xb1\xb2\xb3");
+        assert_eq!(codes(charset, "\u{0}\u{7}"), b"\x00\x07");
+    }
+
+    #[test]
+    fn maps_characters_to_a02_codes() {
+        let charset = Charset::A02;
+        assert_eq!(codes(charset, "café ~ °C"), b"caf\xe9 ~ \xb0C");
+        assert_eq!(codes(charset, "Größe"), b"Gr\xf6\xdfe");
+    }
+
+    #[test]
+    fn transliterates_missing_characters() {
+        assert_eq!(codes(Charset::A00, "é"), b"e");
+        assert_eq!(codes(Charset::A00, "5€ \\~"), b"5EUR ??");
+        assert_eq!(codes(Charset::A02, "→ „ok“"), b"-> \"ok\"");
+        assert_eq!(codes(Charset::A02, "Ω"), b"?");
+        assert_eq!(codes(Charset::A00, "Сок"), b"Co?");
+    }
+
+    #[test]
+    fn substitutes_missing_characters() {
+        let charset = Charset {
+            rom: Rom::A00,
+            fallback: Fallback::Substitute(0xff),
+        };
+        assert_eq!(codes(charset, "é€"), b"\xff\xff");
+        assert_eq!(codes(charset, "ok"), b"ok");
+    }
+}
@@ -0,0 +1,86 @@
+use embedded_hal::blocking::delay::DelayUs;
+use std::time::{Duration, Instant};
+use std::{hint, thread};
+
+/// Delay used by the LCD driving Raspberry Pi buses
+///
+/// `thread::sleep` typically oversleeps by 50 to 100 µs on Linux, which is harmless for the
+/// millisecond waits of the initialization but multiplies the time needed to send each character.
+/// Spinning on a monotonic clock is accurate but keeps a CPU core busy.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{GpioPin::*, Pins, StdDelay, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let mut lcd = LCD::new(Pins {
+///     rs: P26,
+///     rw: None,
+///     enable: P19,
+///     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
+/// })?
+/// .with_delay(StdDelay::HYBRID);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum StdDelay {
+    /// Sleep for the whole delay; the default
+    #[default]
+    Sleep,
+
+    /// Busy-wait for the whole delay
+    Spin,
+
+    /// Busy-wait for delays up to `threshold` µs; sleep for longer ones, busy-waiting for the
+    /// last `threshold` µs so that the wait ends on time
+    Hybrid {
+        /// Longest delay handled without sleeping, in µs
+        threshold: u32,
+    },
+}
+
+impl StdDelay {
+
+    /// Hybrid delay spinning below the typical `thread::sleep` overshoot
+    pub const HYBRID: StdDelay = StdDelay::Hybrid { threshold: 100 };
+}
+
+fn spin_until(deadline: Instant) {
+    while Instant::now() < deadline {
+        hint::spin_loop();
+    }
+}
+
+impl DelayUs<u32> for StdDelay {
+    fn delay_us(&mut self, us: u32) {
+        let duration = Duration::from_micros(us as u64);
+        match *self {
+            StdDelay::Sleep => thread::sleep(duration),
+            StdDelay::Spin => spin_until(Instant::now() + duration),
+            StdDelay::Hybrid { threshold } => {
+                let deadline = Instant::now() + duration;
+                if us > threshold {
+                    thread::sleep(Duration::from_micros((us - threshold) as u64));
+                }
+                spin_until(deadline);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_at_least_requested_time() {
+        for delay in &mut [StdDelay::Sleep, StdDelay::Spin, StdDelay::HYBRID] {
+            for us in &[1, 50, 2000] {
+                let started = Instant::now();
+                delay.delay_us(*us);
+                assert!(started.elapsed() >= Duration::from_micros(*us as u64));
+            }
+        }
+    }
+}
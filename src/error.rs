@@ -0,0 +1,112 @@
+use crate::glyphs::ParseError;
+use core::fmt;
+
+/// Errors returned by the LCD driver
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// GPIO line could not be requested or driven
+    #[cfg(feature = "std")]
+    Gpio(gpio_cdev::errors::Error),
+
+    /// I2C device could not be opened or accessed
+    #[cfg(feature = "std")]
+    I2c(std::io::Error),
+
+    /// SPI device could not be opened or accessed
+    #[cfg(feature = "std")]
+    Spi(std::io::Error),
+
+    /// GPIO chip or line with the given label or name does not exist
+    #[cfg(feature = "std")]
+    NotFound(String),
+
+    /// embedded-hal output pin could not be driven
+    Pin,
+
+    /// Display dimensions are not supported by the controller
+    InvalidGeometry {
+        /// Number of characters per line
+        cols: u8,
+
+        /// Number of lines
+        lines: u8,
+    },
+
+    /// Cursor position is outside of the display
+    InvalidPosition {
+        /// Column
+        col: u8,
+
+        /// Row
+        row: u8,
+    },
+
+    /// Pins do not describe a valid wiring of the display
+    InvalidPinConfig(&'static str),
+
+    /// Custom character could not be defined or shown
+    InvalidGlyph(&'static str),
+
+    /// Text in the glyph file format could not be parsed
+    GlyphFormat(ParseError),
+
+    /// Controller did not clear its busy flag in time
+    BusyTimeout,
+
+    /// Operation is not supported by the bus the display is connected through
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Error::Gpio(e) => write!(f, "GPIO error: {}", e),
+            #[cfg(feature = "std")]
+            Error::I2c(e) => write!(f, "I2C error: {}", e),
+            #[cfg(feature = "std")]
+            Error::Spi(e) => write!(f, "SPI error: {}", e),
+            #[cfg(feature = "std")]
+            Error::NotFound(what) => write!(f, "not found: {}", what),
+            Error::Pin => write!(f, "output pin error"),
+            Error::InvalidGeometry { cols, lines } => {
+                write!(f, "invalid display geometry: {} columns, {} lines", cols, lines)
+            }
+            Error::InvalidPosition { col, row } => {
+                write!(f, "position out of the display: column {}, row {}", col, row)
+            }
+            Error::InvalidPinConfig(reason) => write!(f, "invalid pin configuration: {}", reason),
+            Error::InvalidGlyph(reason) => write!(f, "invalid glyph: {}", reason),
+            Error::GlyphFormat(e) => write!(f, "invalid glyph file: {}", e),
+            Error::BusyTimeout => write!(f, "timeout waiting for the busy flag to clear"),
+            Error::Unsupported(what) => write!(f, "operation not supported by the bus: {}", what),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Gpio(e) => Some(e),
+            Error::I2c(e) => Some(e),
+            Error::Spi(e) => Some(e),
+            Error::GlyphFormat(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<gpio_cdev::errors::Error> for Error {
+    fn from(e: gpio_cdev::errors::Error) -> Self {
+        Error::Gpio(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::GlyphFormat(e)
+    }
+}
@@ -0,0 +1,187 @@
+use crate::Error;
+
+/// Assignment of LCD lines to the output bits of an I/O expander or a shift register
+///
+/// Each field holds the number of the bit (0 being the least significant one) the LCD line is
+/// connected to. The display is always driven in 4-bit mode through d4 to d7.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BitLayout {
+    /// Bit connected to LCD RS pin
+    pub rs: u8,
+
+    /// Bit connected to LCD RW pin; `None` if LCD RW pin is tied to ground
+    pub rw: Option<u8>,
+
+    /// Bit connected to LCD ENABLE pin
+    pub enable: u8,
+
+    /// Bit switching the backlight; `None` if the backlight is not controlled
+    pub backlight: Option<u8>,
+
+    /// `true` if the backlight is on when its bit is low
+    pub backlight_active_low: bool,
+
+    /// Bits connected to LCD DATA pins d4 to d7
+    pub data: [u8; 4],
+}
+
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+impl BitLayout {
+
+    /// Layout of the most common PCF8574 backpacks (YwRobot, LCM1602 and compatible):
+    /// RS=P0, RW=P1, E=P2, backlight=P3, d4 to d7=P4 to P7
+    pub const PCF8574: BitLayout = BitLayout {
+        rs: 0,
+        rw: Some(1),
+        enable: 2,
+        backlight: Some(3),
+        backlight_active_low: false,
+        data: [4, 5, 6, 7],
+    };
+
+    /// Layout of the mjkdz PCF8574 backpacks: d4 to d7=P0 to P3, E=P4, RW=P5, RS=P6,
+    /// backlight=P7 (active low)
+    pub const PCF8574_MJKDZ: BitLayout = BitLayout {
+        rs: 6,
+        rw: Some(5),
+        enable: 4,
+        backlight: Some(7),
+        backlight_active_low: true,
+        data: [0, 1, 2, 3],
+    };
+
+    /// Layout of the Adafruit I2C/SPI character LCD backpack (MCP23008): RS=GP1, E=GP2,
+    /// d4 to d7=GP3 to GP6, backlight=GP7; RW is tied to ground
+    pub const MCP23008_ADAFRUIT: BitLayout = BitLayout {
+        rs: 1,
+        rw: None,
+        enable: 2,
+        backlight: Some(7),
+        backlight_active_low: false,
+        data: [3, 4, 5, 6],
+    };
+
+    /// Layout of the Adafruit RGB LCD shield (MCP23017, port A bits 0 to 7, port B bits 8 to 15):
+    /// RS=GPB7, RW=GPB6, E=GPB5, d4 to d7=GPB4 to GPB1. The RGB backlight is driven separately,
+    /// see [Mcp230xxBus::rgb_shield()](struct.Mcp230xxBus.html#method.rgb_shield).
+    pub const MCP23017_RGB_SHIELD: BitLayout = BitLayout {
+        rs: 15,
+        rw: Some(14),
+        enable: 13,
+        backlight: None,
+        backlight_active_low: false,
+        data: [12, 11, 10, 9],
+    };
+
+    /// Layout of the Adafruit I2C/SPI character LCD backpack in SPI mode (74HC595): RS=Q1,
+    /// E=Q2, d4 to d7=Q6 to Q3, backlight=Q7; RW is tied to ground
+    pub const SHIFT_REGISTER_ADAFRUIT: BitLayout = BitLayout {
+        rs: 1,
+        rw: None,
+        enable: 2,
+        backlight: Some(7),
+        backlight_active_low: false,
+        data: [6, 5, 4, 3],
+    };
+
+    /// Returns `port` with `bit` set to `high`
+    pub(crate) fn set(&self, port: u16, bit: u8, high: bool) -> u16 {
+        if high {
+            port | (1 << bit)
+        } else {
+            port & !(1 << bit)
+        }
+    }
+
+    /// Returns `port` with the data bits set to the 4 least significant bits of `nibble`
+    pub(crate) fn with_data(&self, port: u16, nibble: u8) -> u16 {
+        self.data
+            .iter()
+            .enumerate()
+            .fold(port, |port, (i, bit)| self.set(port, *bit, nibble & (1 << i) != 0))
+    }
+
+    /// Extracts the nibble on the data bits of `port`
+    pub(crate) fn data_of(&self, port: u16) -> u8 {
+        self.data
+            .iter()
+            .enumerate()
+            .fold(0, |nibble, (i, bit)| nibble | ((((port >> bit) & 0x01) as u8) << i))
+    }
+
+    /// Returns `port` with the backlight bit driven to switch the backlight `on` or off
+    pub(crate) fn with_backlight(&self, port: u16, on: bool) -> u16 {
+        match self.backlight {
+            Some(bit) => self.set(port, bit, on != self.backlight_active_low),
+            None => port,
+        }
+    }
+
+    /// Returns the mask of all the bits driving the LCD together with the `extra` bits. Fails with
+    /// `Error::InvalidPinConfig` if a bit is not below `width` or is used twice.
+    pub(crate) fn checked_mask(&self, width: u8, extra: &[u8]) -> Result<u16, Error> {
+        self.bits().chain(extra.iter().copied()).try_fold(0, |mask: u16, bit| {
+            if bit >= width {
+                Err(Error::InvalidPinConfig("bit is out of range of the expander"))
+            } else if mask & (1 << bit) != 0 {
+                Err(Error::InvalidPinConfig("bit is used by more than one line"))
+            } else {
+                Ok(mask | (1 << bit))
+            }
+        })
+    }
+
+    /// Returns the mask of the data bits
+    pub(crate) fn data_mask(&self) -> u16 {
+        self.with_data(0, 0x0f)
+    }
+
+    fn bits(&self) -> impl Iterator<Item = u8> + '_ {
+        let control = [Some(self.rs), Some(self.enable), self.rw, self.backlight];
+        self.data.iter().copied().chain(IntoIterator::into_iter(control).flatten())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_data_bits() {
+        let layout = BitLayout::PCF8574_MJKDZ;
+        let port = layout.with_data(0xf0, 0b1010);
+
+        assert_eq!(port, 0xfa);
+        assert_eq!(layout.data_of(port), 0b1010);
+        assert_eq!(BitLayout::PCF8574.with_data(0x0f, 0b0110), 0x6f);
+    }
+
+    #[test]
+    fn drives_backlight_polarity() {
+        assert_eq!(BitLayout::PCF8574.with_backlight(0, true), 0x08);
+        assert_eq!(BitLayout::PCF8574_MJKDZ.with_backlight(0xff, true), 0x7f);
+        assert_eq!(BitLayout::PCF8574_MJKDZ.with_backlight(0x00, false), 0x80);
+    }
+
+    #[test]
+    fn masks_used_bits() {
+        assert_eq!(BitLayout::PCF8574.checked_mask(8, &[]).unwrap(), 0xff);
+        assert_eq!(BitLayout::MCP23008_ADAFRUIT.checked_mask(8, &[]).unwrap(), 0xfe);
+        assert_eq!(BitLayout::MCP23017_RGB_SHIELD.checked_mask(16, &[]).unwrap(), 0xfe00);
+        assert_eq!(BitLayout::MCP23017_RGB_SHIELD.data_mask(), 0x1e00);
+    }
+
+    #[test]
+    fn checks_used_bits() {
+        assert_eq!(BitLayout::MCP23008_ADAFRUIT.checked_mask(8, &[0]).unwrap(), 0xff);
+
+        let wide = BitLayout { enable: 8, ..BitLayout::MCP23008_ADAFRUIT };
+        assert!(matches!(wide.checked_mask(8, &[]), Err(Error::InvalidPinConfig(_))));
+        assert!(wide.checked_mask(16, &[]).is_ok());
+        let overflow = BitLayout { rs: 16, ..BitLayout::MCP23017_RGB_SHIELD };
+        assert!(matches!(overflow.checked_mask(16, &[]), Err(Error::InvalidPinConfig(_))));
+        let shared = BitLayout { backlight: Some(0), ..BitLayout::PCF8574 };
+        assert!(matches!(shared.checked_mask(8, &[]), Err(Error::InvalidPinConfig(_))));
+        assert!(BitLayout::PCF8574.checked_mask(8, &[3]).is_err());
+    }
+}
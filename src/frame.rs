@@ -0,0 +1,61 @@
+/// Number of characters of the largest display (40x4)
+pub(crate) const FRAME_SIZE: usize = 160;
+
+/// Shadow copy of the display: the characters drawn and the characters the display shows,
+/// indexed by `row * cols + col`
+pub(crate) struct Frame {
+    cells: [u8; FRAME_SIZE],
+    shown: [u8; FRAME_SIZE],
+}
+
+impl Frame {
+
+    pub(crate) fn new() -> Frame {
+        Frame {
+            cells: [b' '; FRAME_SIZE],
+            shown: [b' '; FRAME_SIZE],
+        }
+    }
+
+    /// Fills the frame with spaces; `shown` if the display has been cleared as well
+    pub(crate) fn clear(&mut self, shown: bool) {
+        self.cells = [b' '; FRAME_SIZE];
+        if shown {
+            self.shown = [b' '; FRAME_SIZE];
+        }
+    }
+
+    /// Draws `value` at `index`; `shown` if it has been written to the display as well
+    pub(crate) fn set(&mut self, index: usize, value: u8, shown: bool) {
+        self.cells[index] = value;
+        if shown {
+            self.shown[index] = value;
+        }
+    }
+
+    pub(crate) fn get(&self, index: usize) -> u8 {
+        self.cells[index]
+    }
+
+    /// Returns `true` if the character at `index` differs from the one the display shows
+    pub(crate) fn is_changed(&self, index: usize) -> bool {
+        self.cells[index] != self.shown[index]
+    }
+
+    /// Returns `true` if any character differs from the one the display shows
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.cells != self.shown
+    }
+
+    /// Returns `true` if a character code matching `code` is drawn or shown in the first `cells`
+    /// characters
+    pub(crate) fn shows(&self, cells: usize, code: impl Fn(u8) -> bool) -> bool {
+        let matches = |value: &u8| code(*value);
+        self.cells[..cells].iter().any(matches) || self.shown[..cells].iter().any(matches)
+    }
+
+    /// Records that the character at `index` has been written to the display
+    pub(crate) fn mark_shown(&mut self, index: usize) {
+        self.shown[index] = self.cells[index];
+    }
+}
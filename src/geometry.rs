@@ -0,0 +1,366 @@
+use crate::Error;
+
+/// DDRAM size in 1-line mode
+const ONE_LINE_SIZE: u8 = 80;
+
+/// Address of the second DDRAM line in 2-line mode
+const SECOND_LINE: u8 = 0x40;
+
+/// DDRAM line size in 2-line mode
+const TWO_LINE_SIZE: u8 = 40;
+
+/// Dimensions of the display and the map of its characters to the controller display memory
+///
+/// The character at column `col` of row `row` is stored at DDRAM address
+/// `row_offsets[row] + col`. Rows of displays wired as two halves (e.g. 16x1 "type 1" modules)
+/// continue at `row_offsets[row] + 0x40` from column `split` on. The controller is set to 2-line
+/// mode if any character is stored at address 0x40 or above.
+///
+/// 40x4 modules are made of two controllers, each driving two rows. From row `second_controller`
+/// on, the characters are stored in the display memory of the second controller.
+///
+/// # Examples
+///
+/// ```rust
+/// use rpi_lcd::Geometry;
+///
+/// // 16x1 module using the first 16 characters of both DDRAM lines
+/// let geometry = Geometry {
+///     cols: 16,
+///     rows: 1,
+///     row_offsets: [0x00, 0x00, 0x00, 0x00],
+///     split: Some(8),
+///     second_controller: None,
+/// };
+/// assert_eq!(geometry, Geometry::LCD16X1_TYPE1);
+/// assert_eq!(geometry.address(9, 0), Some(0x41));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Geometry {
+    /// Number of characters per row
+    pub cols: u8,
+
+    /// Number of rows, 1 to 4
+    pub rows: u8,
+
+    /// DDRAM address of the first character of each row
+    pub row_offsets: [u8; 4],
+
+    /// Column from which rows continue in the second DDRAM line; `None` for contiguous rows
+    pub split: Option<u8>,
+
+    /// First row driven by the second controller of dual-controller displays; `None` if the
+    /// display has a single controller
+    pub second_controller: Option<u8>,
+}
+
+impl Geometry {
+
+    /// 8x1 display
+    pub const LCD8X1: Geometry = Geometry::standard_unchecked(8, 1);
+
+    /// 8x2 display
+    pub const LCD8X2: Geometry = Geometry::standard_unchecked(8, 2);
+
+    /// 16x1 "type 2" display, with contiguous characters in 1-line mode
+    pub const LCD16X1: Geometry = Geometry::standard_unchecked(16, 1);
+
+    /// 16x1 "type 1" display, wired as 8x2: characters 8 to 15 are at 0x40 to 0x47
+    pub const LCD16X1_TYPE1: Geometry = Geometry {
+        cols: 16,
+        rows: 1,
+        row_offsets: [0x00; 4],
+        split: Some(8),
+        second_controller: None,
+    };
+
+    /// 16x2 display
+    pub const LCD16X2: Geometry = Geometry::standard_unchecked(16, 2);
+
+    /// 16x4 display
+    pub const LCD16X4: Geometry = Geometry::standard_unchecked(16, 4);
+
+    /// 20x2 display
+    pub const LCD20X2: Geometry = Geometry::standard_unchecked(20, 2);
+
+    /// 20x4 display
+    pub const LCD20X4: Geometry = Geometry::standard_unchecked(20, 4);
+
+    /// 24x2 display
+    pub const LCD24X2: Geometry = Geometry::standard_unchecked(24, 2);
+
+    /// 40x2 display
+    pub const LCD40X2: Geometry = Geometry::standard_unchecked(40, 2);
+
+    /// 40x4 display made of two 40x2 controllers: rows 0 and 1 are driven through E1, rows 2
+    /// and 3 through E2
+    pub const LCD40X4: Geometry = Geometry {
+        cols: 40,
+        rows: 4,
+        row_offsets: [0x00, SECOND_LINE, 0x00, SECOND_LINE],
+        split: None,
+        second_controller: Some(2),
+    };
+
+    /// Returns the geometry of the usual `cols` x `rows` displays: rows 0 and 1 start at 0x00
+    /// and 0x40, rows 2 and 3 continue them at `cols` and 0x40 + `cols`. 40x4 displays use
+    /// two controllers (see [LCD40X4](#associatedconstant.LCD40X4)).
+    ///
+    /// Fails with `Error::InvalidGeometry` if the characters do not fit in the display memory.
+    pub fn standard(cols: u8, rows: u8) -> Result<Geometry, Error> {
+        let geometry = match (cols, rows) {
+            (40, 4) => Geometry::LCD40X4,
+            _ => Geometry::standard_unchecked(cols, rows),
+        };
+        geometry.validate()?;
+        Ok(geometry)
+    }
+
+    const fn standard_unchecked(cols: u8, rows: u8) -> Geometry {
+        Geometry {
+            cols,
+            rows,
+            row_offsets: [0x00, SECOND_LINE, cols, SECOND_LINE.wrapping_add(cols)],
+            split: None,
+            second_controller: None,
+        }
+    }
+
+    /// Checks that every character maps to a distinct valid DDRAM address of its controller.
+    /// Fails with `Error::InvalidGeometry` otherwise.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = Error::InvalidGeometry {
+            cols: self.cols,
+            lines: self.rows,
+        };
+        if self.cols == 0 || self.rows == 0 || self.rows > 4 {
+            return Err(invalid);
+        }
+        if self.split.is_some_and(|split| split == 0 || split >= self.cols) {
+            return Err(invalid);
+        }
+        if self
+            .second_controller
+            .is_some_and(|row| row == 0 || row >= self.rows)
+        {
+            return Err(invalid);
+        }
+
+        let two_lines = self.two_lines();
+        let mut used = [[false; 128]; 2];
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let address = match self.address(col, row) {
+                    Some(address) => address,
+                    None => return Err(invalid),
+                };
+                let valid = if two_lines {
+                    address % SECOND_LINE < TWO_LINE_SIZE && address < 2 * SECOND_LINE
+                } else {
+                    address < ONE_LINE_SIZE
+                };
+                let used = &mut used[self.controller(row) as usize][address as usize];
+                if !valid || *used {
+                    return Err(invalid);
+                }
+                *used = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the DDRAM address of the character at `col` and `row`, or `None` if the
+    /// position is outside of the display.
+    pub fn address(&self, col: u8, row: u8) -> Option<u8> {
+        if col >= self.cols || row >= self.rows || row >= 4 {
+            return None;
+        }
+
+        let offset = self.row_offsets[row as usize];
+        match self.split {
+            Some(split) if col >= split => offset.checked_add(SECOND_LINE + (col - split)),
+            _ => offset.checked_add(col),
+        }
+    }
+
+    /// Returns the column and row of the character stored at DDRAM `address` of `controller`,
+    /// or `None` if the address is not shown on the display.
+    pub fn position(&self, controller: u8, address: u8) -> Option<(u8, u8)> {
+        (0..self.rows.min(4))
+            .filter(|row| self.controller(*row) == controller)
+            .find_map(|row| self.col(row, address).map(|col| (col, row)))
+    }
+
+    /// Returns the column of `row` stored at DDRAM `address`, if any
+    fn col(&self, row: u8, address: u8) -> Option<u8> {
+        let offset = address.checked_sub(self.row_offsets[row as usize])?;
+        let split = self.split.unwrap_or(self.cols).min(self.cols);
+        let col = if offset < split {
+            offset
+        } else {
+            self.split?.checked_add(offset.checked_sub(SECOND_LINE)?)?
+        };
+        if col < self.cols {
+            Some(col)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the DDRAM address the controller moves to after writing at `address`, going
+    /// forward if `increment` is set and backward otherwise.
+    pub(crate) fn next_address(&self, address: u8, increment: bool) -> u8 {
+        let last = if self.two_lines() {
+            SECOND_LINE + TWO_LINE_SIZE - 1
+        } else {
+            ONE_LINE_SIZE - 1
+        };
+        match (self.two_lines(), increment, address) {
+            (_, true, a) if a >= last => 0x00,
+            (true, true, a) if a == TWO_LINE_SIZE - 1 => SECOND_LINE,
+            (_, true, a) => a + 1,
+            (_, false, 0x00) => last,
+            (true, false, SECOND_LINE) => TWO_LINE_SIZE - 1,
+            (_, false, a) => a - 1,
+        }
+    }
+
+    /// Returns the number of controllers of the display, 1 or 2
+    pub fn controllers(&self) -> u8 {
+        match self.second_controller {
+            Some(_) => 2,
+            None => 1,
+        }
+    }
+
+    /// Returns the controller driving `row`, 0 being the first
+    pub fn controller(&self, row: u8) -> u8 {
+        match self.second_controller {
+            Some(first_row) if row >= first_row => 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if the controllers must be set to 2-line mode
+    pub fn two_lines(&self) -> bool {
+        self.split.is_some()
+            || self.row_offsets[..(self.rows.min(4) as usize)]
+                .iter()
+                .any(|offset| *offset >= SECOND_LINE)
+    }
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry::LCD16X2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: [Geometry; 11] = [
+        Geometry::LCD8X1,
+        Geometry::LCD8X2,
+        Geometry::LCD16X1,
+        Geometry::LCD16X1_TYPE1,
+        Geometry::LCD16X2,
+        Geometry::LCD16X4,
+        Geometry::LCD20X2,
+        Geometry::LCD20X4,
+        Geometry::LCD24X2,
+        Geometry::LCD40X2,
+        Geometry::LCD40X4,
+    ];
+
+    #[test]
+    fn presets_are_valid() {
+        for geometry in PRESETS.iter() {
+            assert!(geometry.validate().is_ok(), "{:?}", geometry);
+        }
+    }
+
+    #[test]
+    fn maps_positions_to_addresses() {
+        assert_eq!(Geometry::LCD20X4.address(19, 3), Some(0x67));
+        assert_eq!(Geometry::LCD16X4.address(0, 2), Some(0x10));
+        assert_eq!(Geometry::LCD16X1_TYPE1.address(7, 0), Some(0x07));
+        assert_eq!(Geometry::LCD16X1_TYPE1.address(8, 0), Some(0x40));
+        assert_eq!(Geometry::LCD16X2.address(16, 0), None);
+        assert_eq!(Geometry::LCD16X2.address(0, 2), None);
+        assert!(!Geometry::LCD16X1.two_lines());
+        assert!(Geometry::LCD16X1_TYPE1.two_lines());
+    }
+
+    #[test]
+    fn rejects_geometries_not_fitting_memory() {
+        assert!(Geometry::standard(40, 5).is_err());
+        assert!(Geometry::standard(41, 2).is_err());
+        assert!(Geometry::standard(16, 5).is_err());
+        assert!(Geometry::standard(0, 2).is_err());
+        assert!(Geometry::standard(80, 1).is_ok());
+
+        let overlapping = Geometry {
+            row_offsets: [0x00, 0x40, 0x08, 0x48],
+            ..Geometry::LCD16X4
+        };
+        assert!(overlapping.validate().is_err());
+
+        let single_controller = Geometry {
+            second_controller: None,
+            ..Geometry::LCD40X4
+        };
+        assert!(single_controller.validate().is_err());
+        let no_first_controller_row = Geometry {
+            second_controller: Some(0),
+            ..Geometry::LCD40X4
+        };
+        assert!(no_first_controller_row.validate().is_err());
+    }
+
+    #[test]
+    fn maps_addresses_to_positions() {
+        assert_eq!(Geometry::LCD20X4.position(0, 0x14), Some((0, 2)));
+        assert_eq!(Geometry::LCD16X2.position(0, 0x10), None);
+        assert_eq!(Geometry::LCD40X4.position(1, 0x41), Some((1, 3)));
+        assert_eq!(Geometry::LCD16X1_TYPE1.position(0, 0x40), Some((8, 0)));
+        assert_eq!(Geometry::LCD16X1_TYPE1.position(0, 0x08), None);
+        assert_eq!(Geometry::LCD16X1_TYPE1.position(0, 0x48), None);
+
+        for geometry in PRESETS.iter() {
+            for address in 0..0x80 {
+                for controller in 0..geometry.controllers() {
+                    if let Some((col, row)) = geometry.position(controller, address) {
+                        assert_eq!(geometry.address(col, row), Some(address), "{:?}", geometry);
+                        assert_eq!(geometry.controller(row), controller);
+                    }
+                }
+            }
+            let cells = (0..geometry.controllers())
+                .flat_map(|controller| (0..0x80).map(move |address| (controller, address)))
+                .filter(|(controller, address)| geometry.position(*controller, *address).is_some())
+                .count();
+            assert_eq!(cells, geometry.cols as usize * geometry.rows as usize, "{:?}", geometry);
+        }
+
+        assert_eq!(Geometry::LCD16X2.next_address(0x27, true), 0x40);
+        assert_eq!(Geometry::LCD16X2.next_address(0x67, true), 0x00);
+        assert_eq!(Geometry::LCD16X2.next_address(0x40, false), 0x27);
+        assert_eq!(Geometry::LCD16X1.next_address(0x4f, true), 0x00);
+        assert_eq!(Geometry::LCD16X1.next_address(0x00, false), 0x4f);
+    }
+
+    #[test]
+    fn maps_40x4_rows_to_controllers() {
+        let geometry = Geometry::standard(40, 4).unwrap();
+        assert_eq!(geometry, Geometry::LCD40X4);
+        assert_eq!(geometry.controllers(), 2);
+        assert_eq!(geometry.controller(1), 0);
+        assert_eq!(geometry.controller(2), 1);
+        assert_eq!(geometry.address(39, 1), Some(0x67));
+        assert_eq!(geometry.address(0, 2), Some(0x00));
+        assert_eq!(geometry.address(5, 3), Some(0x45));
+        assert_eq!(Geometry::LCD20X4.controllers(), 1);
+    }
+}
@@ -0,0 +1,614 @@
+//! Glyphs of characters missing from the controller ROM
+//!
+//! [LCD::set_glyphs()](../struct.LCD.html#method.set_glyphs) makes
+//! [print()](../struct.LCD.html#method.print) load the glyphs of the characters the ROM does not
+//! have into the custom character slots. The glyphs are looked up by a [GlyphSource](
+//! type.GlyphSource.html) function; [builtin()](fn.builtin.html) provides the backslash and the
+//! tilde missing from the A00 ROM, Polish letters and the Cyrillic letters that do not look like
+//! Latin ones.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rpi_lcd::glyphs;
+//!
+//! fn glyph(c: char) -> Option<[u8; 8]> {
+//!     match c {
+//!         '✓' => Some([0x00, 0x01, 0x03, 0x16, 0x1c, 0x08, 0x00, 0x00]),
+//!         _ => glyphs::builtin(c),
+//!     }
+//! }
+//!
+//! assert!(glyph('ł').is_some());
+//! ```
+//!
+//! # Glyph files
+//!
+//! Custom characters can be drawn in a text format and [parsed](fn.parse.html) into named
+//! glyphs. Each glyph starts with its name in brackets, followed by its rows: `#` for a dot
+//! that is on and `.` for a dot that is off. A glyph has 8 rows for the 5x8 dots font or 11
+//! rows for the 5x10 dots font. Blank lines and lines starting with `//` are ignored.
+//!
+//! ```text
+//! // 5x8 dots
+//! [bell]
+//! ..#..
+//! .###.
+//! .###.
+//! .###.
+//! #####
+//! .....
+//! ..#..
+//! .....
+//! ```
+//!
+//! [include_glyphs!](../macro.include_glyphs.html) embeds a glyph file in the program at compile
+//! time; [ICONS](constant.ICONS.html) is such a file with a few common icons.
+
+use crate::{CharSize, Error};
+use core::fmt;
+use core::iter::Enumerate;
+use core::str::Lines;
+
+/// Function returning the 5x8 glyph of a character, one byte per row, or `None` if it has none
+pub type GlyphSource = fn(char) -> Option<[u8; 8]>;
+
+/// Bitmap of a custom character, one byte per row: 8 rows for the 5x8 dots font or 11 rows for
+/// the 5x10 dots font. The five least significant bits of each byte determine the pixels in that
+/// row.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Glyph {
+    rows: [u8; 11],
+    height: u8,
+}
+
+impl Glyph {
+
+    /// Creates the glyph made of `rows`
+    ///
+    /// Fails with `Error::InvalidGlyph` if there are neither 8 nor 11 rows or if a row is wider
+    /// than 5 dots.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rpi_lcd::{glyphs::Glyph, CharSize};
+    ///
+    /// let rows = [0x04, 0x0e, 0x1f, 0x0e, 0x04, 0x00, 0x00, 0x00, 0x04, 0x04, 0x00];
+    /// let glyph = Glyph::new(&rows)?;
+    /// assert_eq!(glyph.char_size(), CharSize::Dots5x10);
+    /// assert!(Glyph::new(&[0x20; 8]).is_err());
+    /// # Ok::<(), rpi_lcd::Error>(())
+    /// ```
+    pub fn new(rows: &[u8]) -> Result<Glyph, Error> {
+        if rows.len() != 8 && rows.len() != 11 {
+            return Err(Error::InvalidGlyph("glyph must have 8 or 11 rows"));
+        }
+        if rows.iter().any(|row| row & !0x1f != 0) {
+            return Err(Error::InvalidGlyph("glyph rows must be 5 dots wide"));
+        }
+
+        let mut glyph = Glyph {
+            rows: [0; 11],
+            height: rows.len() as u8,
+        };
+        glyph.rows[..rows.len()].copy_from_slice(rows);
+        Ok(glyph)
+    }
+
+    /// Returns the rows of the glyph
+    pub fn rows(&self) -> &[u8] {
+        &self.rows[..self.height as usize]
+    }
+
+    /// Returns the font the glyph is drawn for
+    pub fn char_size(&self) -> CharSize {
+        if self.height == 8 {
+            CharSize::Dots5x8
+        } else {
+            CharSize::Dots5x10
+        }
+    }
+}
+
+impl From<[u8; 8]> for Glyph {
+    fn from(rows: [u8; 8]) -> Glyph {
+        let mut glyph = Glyph {
+            rows: [0; 11],
+            height: 8,
+        };
+        glyph.rows[..8].copy_from_slice(&rows);
+        glyph
+    }
+}
+
+impl From<[u8; 11]> for Glyph {
+    fn from(rows: [u8; 11]) -> Glyph {
+        Glyph { rows, height: 11 }
+    }
+}
+
+/// Maximum number of named glyphs
+pub(crate) const NAMED_GLYPHS: usize = 32;
+
+/// Maximum length of a glyph name in bytes
+pub(crate) const NAME_LENGTH: usize = 16;
+
+#[derive(Clone, Copy)]
+struct Named {
+    name: [u8; NAME_LENGTH],
+    length: u8,
+    pattern: Glyph,
+}
+
+impl Named {
+
+    fn name(&self) -> &[u8] {
+        &self.name[..self.length as usize]
+    }
+}
+
+/// Glyphs registered by name through
+/// [LCD::register_glyph()](../struct.LCD.html#method.register_glyph)
+pub(crate) struct Registry {
+    entries: [Option<Named>; NAMED_GLYPHS],
+}
+
+impl Registry {
+
+    pub(crate) fn new() -> Registry {
+        Registry {
+            entries: [None; NAMED_GLYPHS],
+        }
+    }
+
+    /// Registers `pattern` as `name`, replacing the glyph registered under the same name
+    pub(crate) fn insert(&mut self, name: &str, pattern: Glyph) -> Result<(), Error> {
+        if !is_valid_name(name) {
+            return Err(Error::InvalidGlyph("name must be 1 to 16 bytes without braces or spaces"));
+        }
+
+        let mut named = Named {
+            name: [0; NAME_LENGTH],
+            length: name.len() as u8,
+            pattern,
+        };
+        named.name[..name.len()].copy_from_slice(name.as_bytes());
+
+        let index = self
+            .entries
+            .iter()
+            .position(|e| matches!(e, Some(e) if e.name() == name.as_bytes()))
+            .or_else(|| self.entries.iter().position(Option::is_none))
+            .ok_or(Error::InvalidGlyph("too many named glyphs"))?;
+        self.entries[index] = Some(named);
+        Ok(())
+    }
+
+    /// Returns the glyph registered as `name`
+    pub(crate) fn get(&self, name: &str) -> Option<Glyph> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|e| e.name() == name.as_bytes())
+            .map(|e| e.pattern)
+    }
+
+    /// Returns the glyph referenced by the `{name}` escape `text` starts with and the length of
+    /// the escape
+    pub(crate) fn escape(&self, text: &str) -> Option<(Glyph, usize)> {
+        if !text.starts_with('{') {
+            return None;
+        }
+        let end = text[1..].find(['{', '}', ' ', '\n'])? + 1;
+        if !text[end..].starts_with('}') {
+            return None;
+        }
+        self.get(&text[1..end]).map(|pattern| (pattern, end + 1))
+    }
+}
+
+/// Returns `true` if `name` can be registered and printed with a `{name}` escape
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= NAME_LENGTH
+        && !name.contains(|c: char| c == '{' || c == '}' || c.is_whitespace())
+}
+
+/// Icons in the [glyph file format](index.html#glyph-files): `bell`, `heart`, `smiley`, `check`,
+/// `up`, `down`, `battery` and `lock`
+pub const ICONS: &str = include_str!("icons.glyphs");
+
+/// Parses the named glyphs of `text`, written in the [glyph file format](index.html#glyph-files)
+///
+/// The glyphs are returned in the order of the text. Parsing stops at the first error.
+///
+/// # Examples
+///
+/// ```rust
+/// # use rpi_lcd::{glyphs, mock::MockBus, CharSize, Mode, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// # let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+/// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+/// for glyph in glyphs::parse(glyphs::ICONS) {
+///     let (name, glyph) = glyph?;
+///     lcd.register_glyph(name, glyph)?;
+/// }
+/// lcd.print("{battery} 85%")?;
+///
+/// let error = glyphs::parse("[wide]\n######").next().unwrap().unwrap_err();
+/// assert_eq!(error.to_string(), "line 2: row is 6 dots wide, expected 5");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse(text: &str) -> Parse<'_> {
+    Parse {
+        lines: text.lines().enumerate(),
+        header: None,
+        failed: false,
+    }
+}
+
+/// Embeds a file in the [glyph file format](glyphs/index.html#glyph-files) at compile time and
+/// returns the [parser](glyphs/fn.parse.html) of its glyphs
+///
+/// The path is relative to the file the macro is called in, as for `include_str!`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// for glyph in rpi_lcd::include_glyphs!("icons.glyphs") {
+///     let (name, glyph) = glyph?;
+///     lcd.register_glyph(name, glyph)?;
+/// }
+/// ```
+#[macro_export]
+macro_rules! include_glyphs {
+    ($path:expr) => {
+        $crate::glyphs::parse(include_str!($path))
+    };
+}
+
+/// Iterator over the named glyphs of a text, returned by [parse()](fn.parse.html)
+#[derive(Debug, Clone)]
+pub struct Parse<'a> {
+    lines: Enumerate<Lines<'a>>,
+    header: Option<(usize, &'a str)>,
+    failed: bool,
+}
+
+impl<'a> Parse<'a> {
+
+    /// Returns the next line that is neither blank nor a comment, numbered from 1
+    fn next_line(&mut self) -> Option<(usize, &'a str)> {
+        self.lines
+            .by_ref()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .find(|(_, line)| !line.is_empty() && !line.starts_with("//"))
+    }
+
+    fn glyph(&mut self) -> Option<Result<(&'a str, Glyph), ParseError>> {
+        let (header_line, header) = match self.header.take() {
+            Some(header) => header,
+            None => self.next_line()?,
+        };
+        let name = match header.strip_prefix('[') {
+            Some(rest) => rest.strip_suffix(']').filter(|name| is_valid_name(name)),
+            None => return Some(Err(ParseError::new(header_line, ParseErrorKind::MissingName))),
+        };
+        let name = match name {
+            Some(name) => name,
+            None => return Some(Err(ParseError::new(header_line, ParseErrorKind::InvalidName))),
+        };
+
+        let mut rows = [0u8; 11];
+        let mut count = 0;
+        while let Some((number, line)) = self.next_line() {
+            if line.starts_with('[') {
+                self.header = Some((number, line));
+                break;
+            }
+            let row = match parse_row(line) {
+                Ok(row) => row,
+                Err(kind) => return Some(Err(ParseError::new(number, kind))),
+            };
+            if count == rows.len() {
+                let kind = ParseErrorKind::RowCount(count + 1);
+                return Some(Err(ParseError::new(header_line, kind)));
+            }
+            rows[count] = row;
+            count += 1;
+        }
+
+        Some(match Glyph::new(&rows[..count]) {
+            Ok(glyph) => Ok((name, glyph)),
+            Err(_) => Err(ParseError::new(header_line, ParseErrorKind::RowCount(count))),
+        })
+    }
+}
+
+impl<'a> Iterator for Parse<'a> {
+    type Item = Result<(&'a str, Glyph), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let glyph = self.glyph()?;
+        self.failed = glyph.is_err();
+        Some(glyph)
+    }
+}
+
+/// Returns the dots of a row made of `#` and `.`
+fn parse_row(line: &str) -> Result<u8, ParseErrorKind> {
+    let mut row = 0u8;
+    for c in line.chars() {
+        let dot = match c {
+            '#' => 1,
+            '.' => 0,
+            _ => return Err(ParseErrorKind::InvalidDot(c)),
+        };
+        row = (row << 1) | dot;
+    }
+
+    match line.len() {
+        5 => Ok(row),
+        width => Err(ParseErrorKind::RowWidth(width)),
+    }
+}
+
+/// Error in a text in the [glyph file format](index.html#glyph-files)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ParseError {
+    /// Line of the error, numbered from 1
+    pub line: usize,
+
+    /// What is wrong with the line
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+
+    fn new(line: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { line, kind }
+    }
+}
+
+/// Kind of a [ParseError](struct.ParseError.html)
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// Rows come before the first `[name]` line
+    MissingName,
+
+    /// Name line is not `[name]` with a name of 1 to 16 bytes without braces or whitespace
+    InvalidName,
+
+    /// Row does not have 5 dots; holds the number of dots
+    RowWidth(usize),
+
+    /// Row has a character other than `#` and `.`
+    InvalidDot(char),
+
+    /// Glyph has neither 8 nor 11 rows; holds the number of rows. The line is the one of the
+    /// glyph name.
+    RowCount(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ParseErrorKind::MissingName => write!(f, "rows before the first [name] line"),
+            ParseErrorKind::InvalidName => write!(
+                f,
+                "expected [name] with a name of 1 to 16 bytes without braces or whitespace"
+            ),
+            ParseErrorKind::RowWidth(width) => {
+                write!(f, "row is {} dots wide, expected 5", width)
+            }
+            ParseErrorKind::InvalidDot(c) => {
+                write!(f, "unexpected {:?} in row, expected '#' or '.'", c)
+            }
+            ParseErrorKind::RowCount(rows) => write!(
+                f,
+                "glyph has {} rows, expected 8 (5x8 dots font) or 11 (5x10 dots font)",
+                rows
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Returns the built-in glyph of `c`
+pub fn builtin(c: char) -> Option<[u8; 8]> {
+    BUILTIN
+        .iter()
+        .find(|(glyph_char, _)| *glyph_char == c)
+        .map(|(_, glyph)| *glyph)
+}
+
+const BUILTIN: &[(char, [u8; 8])] = &[
+    // ASCII characters whose codes show ¥ and → on the A00 ROM
+    ('\\', [0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000, 0b00000]),
+    ('~', [0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000, 0b00000]),
+    // Polish letters
+    ('ą', [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111, 0b00010]),
+    ('Ą', [0b01110, 0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b00010]),
+    ('ć', [0b00010, 0b00100, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110, 0b00000]),
+    ('Ć', [0b00010, 0b00100, 0b01110, 0b10001, 0b10000, 0b10001, 0b01110, 0b00000]),
+    ('ę', [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110, 0b00100]),
+    ('Ę', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111, 0b00010]),
+    ('ł', [0b01100, 0b00100, 0b00110, 0b01100, 0b00100, 0b00100, 0b01110, 0b00000]),
+    ('Ł', [0b10000, 0b10000, 0b10100, 0b11000, 0b10000, 0b10000, 0b11111, 0b00000]),
+    ('ń', [0b00010, 0b00100, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001, 0b00000]),
+    ('Ń', [0b00010, 0b00100, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b00000]),
+    ('ó', [0b00010, 0b00100, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110, 0b00000]),
+    ('Ó', [0b00010, 0b00100, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110, 0b00000]),
+    ('ś', [0b00010, 0b00100, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110, 0b00000]),
+    ('Ś', [0b00010, 0b00100, 0b01111, 0b10000, 0b01110, 0b00001, 0b11110, 0b00000]),
+    ('ź', [0b00010, 0b00100, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000]),
+    ('Ź', [0b00010, 0b00100, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000]),
+    ('ż', [0b00100, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000]),
+    ('Ż', [0b00100, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111, 0b00000]),
+    // Cyrillic letters without a Latin look-alike
+    ('Б', [0b11111, 0b10000, 0b10000, 0b11110, 0b10001, 0b10001, 0b11110, 0b00000]),
+    ('Г', [0b11111, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b00000]),
+    ('Д', [0b00110, 0b01010, 0b01010, 0b01010, 0b01010, 0b11111, 0b10001, 0b00000]),
+    ('Ж', [0b10101, 0b10101, 0b10101, 0b01110, 0b10101, 0b10101, 0b10101, 0b00000]),
+    ('З', [0b01110, 0b10001, 0b00001, 0b00110, 0b00001, 0b10001, 0b01110, 0b00000]),
+    ('И', [0b10001, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b10001, 0b00000]),
+    ('Й', [0b01010, 0b00100, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b00000]),
+    ('Л', [0b00111, 0b01001, 0b01001, 0b01001, 0b01001, 0b01001, 0b10001, 0b00000]),
+    ('П', [0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b00000]),
+    ('У', [0b10001, 0b10001, 0b10001, 0b01111, 0b00001, 0b10001, 0b01110, 0b00000]),
+    ('Ф', [0b00100, 0b01110, 0b10101, 0b10101, 0b10101, 0b01110, 0b00100, 0b00000]),
+    ('Ц', [0b10010, 0b10010, 0b10010, 0b10010, 0b10010, 0b10010, 0b11111, 0b00001]),
+    ('Ч', [0b10001, 0b10001, 0b10001, 0b01111, 0b00001, 0b00001, 0b00001, 0b00000]),
+    ('Ш', [0b10101, 0b10101, 0b10101, 0b10101, 0b10101, 0b10101, 0b11111, 0b00000]),
+    ('Щ', [0b10101, 0b10101, 0b10101, 0b10101, 0b10101, 0b10101, 0b11111, 0b00001]),
+    ('Ъ', [0b11000, 0b01000, 0b01000, 0b01110, 0b01001, 0b01001, 0b01110, 0b00000]),
+    ('Ы', [0b10001, 0b10001, 0b10001, 0b11001, 0b10101, 0b10101, 0b11001, 0b00000]),
+    ('Ь', [0b10000, 0b10000, 0b10000, 0b11110, 0b10001, 0b10001, 0b11110, 0b00000]),
+    ('Э', [0b01110, 0b10001, 0b00001, 0b00111, 0b00001, 0b10001, 0b01110, 0b00000]),
+    ('Ю', [0b10010, 0b10101, 0b10101, 0b11101, 0b10101, 0b10101, 0b10010, 0b00000]),
+    ('Я', [0b01111, 0b10001, 0b10001, 0b01111, 0b00101, 0b01001, 0b10001, 0b00000]),
+    ('Ё', [0b01010, 0b00000, 0b11111, 0b10000, 0b11110, 0b10000, 0b11111, 0b00000]),
+    ('б', [0b00011, 0b01100, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110, 0b00000]),
+    ('в', [0b00000, 0b00000, 0b11110, 0b10001, 0b11110, 0b10001, 0b11110, 0b00000]),
+    ('г', [0b00000, 0b00000, 0b11111, 0b10000, 0b10000, 0b10000, 0b10000, 0b00000]),
+    ('д', [0b00000, 0b00000, 0b00110, 0b01010, 0b01010, 0b11111, 0b10001, 0b00000]),
+    ('ж', [0b00000, 0b00000, 0b10101, 0b10101, 0b01110, 0b10101, 0b10101, 0b00000]),
+    ('з', [0b00000, 0b00000, 0b01110, 0b10001, 0b00110, 0b10001, 0b01110, 0b00000]),
+    ('и', [0b00000, 0b00000, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b00000]),
+    ('й', [0b01010, 0b00100, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b00000]),
+    ('к', [0b00000, 0b00000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b00000]),
+    ('л', [0b00000, 0b00000, 0b00111, 0b01001, 0b01001, 0b01001, 0b10001, 0b00000]),
+    ('м', [0b00000, 0b00000, 0b10001, 0b11011, 0b10101, 0b10001, 0b10001, 0b00000]),
+    ('н', [0b00000, 0b00000, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b00000]),
+    ('п', [0b00000, 0b00000, 0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b00000]),
+    ('т', [0b00000, 0b00000, 0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000]),
+    ('ф', [0b00000, 0b00100, 0b01110, 0b10101, 0b10101, 0b01110, 0b00100, 0b00000]),
+    ('ц', [0b00000, 0b00000, 0b10010, 0b10010, 0b10010, 0b10010, 0b11111, 0b00001]),
+    ('ч', [0b00000, 0b00000, 0b10001, 0b10001, 0b01111, 0b00001, 0b00001, 0b00000]),
+    ('ш', [0b00000, 0b00000, 0b10101, 0b10101, 0b10101, 0b10101, 0b11111, 0b00000]),
+    ('щ', [0b00000, 0b00000, 0b10101, 0b10101, 0b10101, 0b10101, 0b11111, 0b00001]),
+    ('ъ', [0b00000, 0b00000, 0b11000, 0b01000, 0b01110, 0b01001, 0b01110, 0b00000]),
+    ('ы', [0b00000, 0b00000, 0b10001, 0b10001, 0b11101, 0b10011, 0b11101, 0b00000]),
+    ('ь', [0b00000, 0b00000, 0b10000, 0b10000, 0b11110, 0b10001, 0b11110, 0b00000]),
+    ('э', [0b00000, 0b00000, 0b01110, 0b10001, 0b00111, 0b10001, 0b01110, 0b00000]),
+    ('ю', [0b00000, 0b00000, 0b10010, 0b10101, 0b11101, 0b10101, 0b10010, 0b00000]),
+    ('я', [0b00000, 0b00000, 0b01111, 0b10001, 0b01111, 0b00101, 0b01001, 0b00000]),
+    ('ё', [0b01010, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110, 0b00000]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    #[test]
+    fn registry_finds_escapes() {
+        let mut registry = Registry::new();
+        registry.insert("smiley", [1; 8].into()).unwrap();
+        registry.insert("bell", [2; 8].into()).unwrap();
+        registry.insert("smiley", [3; 8].into()).unwrap();
+
+        assert_eq!(registry.escape("{smiley} ok"), Some(([3; 8].into(), 8)));
+        assert_eq!(registry.escape("{bell}"), Some(([2; 8].into(), 6)));
+        assert_eq!(registry.escape("{bel}"), None);
+        assert_eq!(registry.escape("{bell"), None);
+        assert_eq!(registry.escape("{{bell}"), None);
+        assert_eq!(registry.escape(" {bell}"), None);
+
+        assert!(matches!(registry.insert("", [0; 8].into()), Err(Error::InvalidGlyph(_))));
+        assert!(matches!(registry.insert("a b", [0; 8].into()), Err(Error::InvalidGlyph(_))));
+        assert!(matches!(registry.insert("{a}", [0; 8].into()), Err(Error::InvalidGlyph(_))));
+        let long = registry.insert("a_very_long_glyph_name", [0; 8].into());
+        assert!(matches!(long, Err(Error::InvalidGlyph(_))));
+        for i in 0..NAMED_GLYPHS - 2 {
+            registry.insert(&format!("g{}", i), [0; 8].into()).unwrap();
+        }
+        let full = registry.insert("one_too_many", [0; 8].into());
+        assert!(matches!(full, Err(Error::InvalidGlyph(_))));
+    }
+
+    #[test]
+    fn parses_glyph_files() {
+        let text = "
+            // lines are trimmed
+            [dot]
+            .....
+            .....
+            .###.
+            .###.
+            .###.
+            .....
+            .....
+            .....
+
+            [tall]
+            #....
+            .#...
+            ..#..
+            ...#.
+            ....#
+            .....
+            .....
+            .....
+            .....
+            .....
+            #####
+        ";
+        let glyphs: Vec<_> = parse(text).collect::<Result<_, _>>().unwrap();
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[0].0, "dot");
+        assert_eq!(glyphs[0].1, Glyph::from([0, 0, 0x0e, 0x0e, 0x0e, 0, 0, 0]));
+        assert_eq!(glyphs[1].0, "tall");
+        assert_eq!(glyphs[1].1.char_size(), CharSize::Dots5x10);
+        assert_eq!(glyphs[1].1.rows()[..5], [0x10, 0x08, 0x04, 0x02, 0x01]);
+        assert_eq!(glyphs[1].1.rows()[10], 0x1f);
+
+        let icons = parse(ICONS).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(icons.len(), 8);
+        assert!(icons.iter().all(|(_, glyph)| glyph.char_size() == CharSize::Dots5x8));
+        assert_eq!(crate::include_glyphs!("icons.glyphs").count(), icons.len());
+    }
+
+    #[test]
+    fn reports_glyph_file_errors() {
+        let error = |text: &str| parse(text).find_map(Result::err).unwrap();
+        let rows = "\n.....".repeat(8);
+
+        assert_eq!(error("....."), ParseError::new(1, ParseErrorKind::MissingName));
+        assert_eq!(error("\n[a b]"), ParseError::new(2, ParseErrorKind::InvalidName));
+        assert_eq!(error("[dot"), ParseError::new(1, ParseErrorKind::InvalidName));
+        assert_eq!(error("[dot]\n...."), ParseError::new(2, ParseErrorKind::RowWidth(4)));
+        assert_eq!(error("[dot]\n..o.."), ParseError::new(2, ParseErrorKind::InvalidDot('o')));
+        assert_eq!(error("[dot]\n.....\n[x]"), ParseError::new(1, ParseErrorKind::RowCount(1)));
+        let twelve = format!("[ok]{}\n[long]{}{}", rows, rows, "\n.....".repeat(4));
+        assert_eq!(error(&twelve), ParseError::new(10, ParseErrorKind::RowCount(12)));
+
+        // parsing stops at the first error
+        let text = format!("[bad]\n....\n[ok]{}", rows);
+        assert_eq!(parse(&text).count(), 1);
+        assert_eq!(
+            error("[dot]\n......").to_string(),
+            "line 2: row is 6 dots wide, expected 5"
+        );
+    }
+
+    #[test]
+    fn builtin_glyphs_fit_5_columns() {
+        for (c, glyph) in BUILTIN {
+            assert!(glyph.iter().all(|row| row & !0x1f == 0), "{}", c);
+            assert_eq!(BUILTIN.iter().filter(|(other, _)| other == c).count(), 1, "{}", c);
+        }
+        assert!(builtin('ł').is_some());
+        assert!(builtin('a').is_none());
+    }
+}
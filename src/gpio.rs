@@ -0,0 +1,522 @@
+use crate::{Bus, Error, Mode, Register};
+use gpio_cdev::*;
+use std::path::PathBuf;
+
+const DATA_PINS: usize = 8;
+
+/// Raspberry Pi GPIO pin references
+///
+/// The values of this enum are used to indicate in [Pins](struct.Pins.html) which GPIO pin is
+/// connected to which LCD pin. Use `GpioPin::NONE` for LCD data pins 0 to 3 to indicate the LCD
+/// works in 4-bit mode.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum GpioPin {
+    NONE = -1,
+    P0 = 0,
+    P1 = 1,
+    P2 = 2,
+    P3 = 3,
+    P4 = 4,
+    P5 = 5,
+    P6 = 6,
+    P7 = 7,
+    P8 = 8,
+    P9 = 9,
+    P10 = 10,
+    P11 = 11,
+    P12 = 12,
+    P13 = 13,
+    P14 = 14,
+    P15 = 15,
+    P16 = 16,
+    P17 = 17,
+    P18 = 18,
+    P19 = 19,
+    P20 = 20,
+    P21 = 21,
+    P22 = 22,
+    P23 = 23,
+    P24 = 24,
+    P25 = 25,
+    P26 = 26,
+    P27 = 27,
+}
+
+trait OutputPin {
+    fn write(&self, value: GpioPinSignal) -> Result<(), Error>;
+}
+
+impl OutputPin for LineHandle {
+    fn write(&self, value: GpioPinSignal) -> Result<(), Error> {
+        Ok(self.set_value(value as u8)?)
+    }
+}
+
+#[derive(Debug)]
+enum GpioPinSignal {
+    High = 0x01,
+    Low = 0x00,
+}
+
+impl From<bool> for GpioPinSignal {
+    fn from(high: bool) -> Self {
+        if high {
+            GpioPinSignal::High
+        } else {
+            GpioPinSignal::Low
+        }
+    }
+}
+
+/// LCD pins
+///
+/// To inidicate that LCD works in 4-bit mode use `GpioPin::NONE` as the first
+/// 4 items of `data` pins array.
+#[derive(Debug)]
+pub struct Pins {
+    /// GPIO pin connected to LCD RS pin
+    pub rs: GpioPin,
+
+    /// GPIO pin connected to LCD RW pin; may be set to `None` if LCD RW pin is not used
+    pub rw: Option<GpioPin>,
+
+    /// GPIO pin connected to LCD ENABLE pin
+    pub enable: GpioPin,
+
+    /// GPIO pins connected to LCD DATA pins d0 to d7
+    ///
+    /// Set the first 4 items of this array to `GpioPin::NONE` to indicate LCD
+    /// is working in 4-bit mode.
+    pub data: [GpioPin; DATA_PINS],
+}
+
+/// GPIO chip the LCD lines belong to
+#[derive(Debug, PartialEq, Clone)]
+pub enum GpioChip {
+    /// Character device of the chip, e.g. `/dev/gpiochip4`
+    Path(PathBuf),
+
+    /// Label of the chip, e.g. `pinctrl-rp1` on Raspberry Pi 5
+    Label(String),
+
+    /// Number of the chip; `Number(n)` stands for `/dev/gpiochip<n>`
+    Number(u32),
+}
+
+impl Default for GpioChip {
+    fn default() -> Self {
+        GpioChip::Number(0)
+    }
+}
+
+impl GpioChip {
+
+    pub(crate) fn open(&self) -> Result<Chip, Error> {
+        match self {
+            GpioChip::Path(path) => Ok(Chip::new(path)?),
+            GpioChip::Number(n) => Ok(Chip::new(format!("/dev/gpiochip{}", n))?),
+            GpioChip::Label(label) => {
+                for chip in chips()? {
+                    let chip = chip?;
+                    if chip.label() == label {
+                        return Ok(chip);
+                    }
+                }
+                Err(Error::NotFound(format!("GPIO chip labeled {}", label)))
+            }
+        }
+    }
+}
+
+/// GPIO line of a [GpioChip](enum.GpioChip.html)
+#[derive(Debug, PartialEq, Clone)]
+pub enum GpioLine {
+    /// Offset of the line within the chip
+    Offset(u32),
+
+    /// Name of the line, as given by the device tree (e.g. `GPIO17`)
+    Name(String),
+}
+
+impl GpioLine {
+
+    pub(crate) fn line_handle(&self, chip: &mut Chip, consumer: &str) -> Result<LineHandle, Error> {
+        Ok(self.line(chip)?.request(LineRequestFlags::OUTPUT, 1, consumer)?)
+    }
+
+    fn line(&self, chip: &mut Chip) -> Result<Line, Error> {
+        match self {
+            GpioLine::Offset(offset) => Ok(chip.get_line(*offset)?),
+            GpioLine::Name(name) => chip
+                .lines()
+                .find(|line| {
+                    line.info()
+                        .map(|info| info.name() == Some(name.as_str()))
+                        .unwrap_or(false)
+                })
+                .ok_or_else(|| Error::NotFound(format!("GPIO line named {}", name))),
+        }
+    }
+}
+
+impl From<u32> for GpioLine {
+    fn from(offset: u32) -> Self {
+        GpioLine::Offset(offset)
+    }
+}
+
+impl From<&str> for GpioLine {
+    fn from(name: &str) -> Self {
+        GpioLine::Name(name.to_string())
+    }
+}
+
+/// LCD lines of an arbitrary GPIO chip
+///
+/// Unlike [Pins](struct.Pins.html), which are limited to the header of the classic Raspberry Pi
+/// boards, the lines may belong to any chip and be given by offset or by name.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{GpioChip, LinePins, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let mut lcd = LCD::with_line_pins(LinePins {
+///     chip: GpioChip::Label("pinctrl-rp1".to_string()),
+///     rs: 26.into(),
+///     rw: None,
+///     enable: 19.into(),
+///     data: [None, None, None, None, Some(13.into()), Some(6.into()), Some("GPIO5".into()),
+///         Some("GPIO11".into())],
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinePins {
+    /// Chip the lines belong to
+    pub chip: GpioChip,
+
+    /// Line connected to LCD RS pin
+    pub rs: GpioLine,
+
+    /// Line connected to LCD RW pin; `None` if LCD RW pin is not used
+    pub rw: Option<GpioLine>,
+
+    /// Line connected to LCD ENABLE pin
+    pub enable: GpioLine,
+
+    /// Lines connected to LCD DATA pins d0 to d7; d0 to d3 are `None` if LCD works in 4-bit mode
+    pub data: [Option<GpioLine>; DATA_PINS],
+}
+
+/// [Bus](trait.Bus.html) implementation for LCD connected directly to Raspberry Pi GPIO pins
+///
+/// The data lines are requested together and set with a single call, so that they change at
+/// once. When RW is not connected, RS is requested with the data lines as well and its level is
+/// applied with the next data write.
+///
+/// 40x4 displays have a second ENABLE line (E2) for the controller driving the lower two rows;
+/// request it with [with_second_enable()](#method.with_second_enable). Displays sharing the RS,
+/// RW and data lines with individual ENABLE lines are driven through a
+/// [SharedBus](struct.SharedBus.html).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{CharSize, GpioBus, GpioPin::*, Pins, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let bus = GpioBus::new(Pins {
+///     rs: P26,
+///     rw: None,
+///     enable: P19,
+///     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
+/// })?
+/// .with_second_enable(21.into())?;
+///
+/// let mut lcd = LCD::with_bus(bus);
+/// lcd.begin(40, 4, CharSize::Dots5x8)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct GpioBus {
+    rs: Option<LineHandle>,
+    rw: Option<LineHandle>,
+    enables: Vec<LineHandle>,
+    controller: u8,
+    data: DataLines,
+    values: Vec<u8>,
+    mode: Mode,
+    reading: bool,
+}
+
+impl GpioBus {
+
+    /// Requests the GPIO lines described by `pins` from `/dev/gpiochip0`.
+    ///
+    /// Fails with `Error::InvalidPinConfig` if `rs`, `enable` or data pins d4 to d7 are
+    /// `GpioPin::NONE`, or if only some of data pins d0 to d3 are connected.
+    pub fn new(pins: Pins) -> Result<GpioBus, Error> {
+        if pins.rs == GpioPin::NONE || pins.enable == GpioPin::NONE {
+            return Err(Error::InvalidPinConfig("rs and enable pins are required"));
+        }
+        if pins.rw == Some(GpioPin::NONE) {
+            return Err(Error::InvalidPinConfig("unused rw pin must be set to None"));
+        }
+
+        let line = |pin: GpioPin| match pin {
+            GpioPin::NONE => None,
+            pin => Some(GpioLine::Offset(pin as u32)),
+        };
+        let mut data: [Option<GpioLine>; DATA_PINS] = Default::default();
+        for (line_data, pin) in data.iter_mut().zip(&pins.data) {
+            *line_data = line(*pin);
+        }
+
+        GpioBus::with_line_pins(LinePins {
+            chip: GpioChip::default(),
+            rs: GpioLine::Offset(pins.rs as u32),
+            rw: pins.rw.map(|pin| GpioLine::Offset(pin as u32)),
+            enable: GpioLine::Offset(pins.enable as u32),
+            data,
+        })
+    }
+
+    /// Requests the lines described by `pins` from their GPIO chip.
+    ///
+    /// Fails with `Error::InvalidPinConfig` if data lines d4 to d7 are missing or if only some
+    /// of data lines d0 to d3 are connected, and with `Error::NotFound` if the chip or a named
+    /// line does not exist.
+    pub fn with_line_pins(pins: LinePins) -> Result<GpioBus, Error> {
+        if pins.data[4..].iter().any(Option::is_none) {
+            return Err(Error::InvalidPinConfig("data pins d4 to d7 are required"));
+        }
+        let low_pins = pins.data[..4].iter().filter(|p| p.is_some()).count();
+        if low_pins != 0 && low_pins != 4 {
+            return Err(Error::InvalidPinConfig("data pins d0 to d3 must be all set or all NONE"));
+        }
+
+        let mut chip = pins.chip.open()?;
+
+        let mut offsets = Vec::with_capacity(DATA_PINS + 1);
+        for line in pins.data.iter().flatten() {
+            offsets.push(line.line(&mut chip)?.offset());
+        }
+        let mode = if offsets.len() == DATA_PINS {
+            Mode::Bits8
+        } else {
+            Mode::Bits4
+        };
+
+        let (rs, rw) = match &pins.rw {
+            Some(line) => (
+                Some(pins.rs.line_handle(&mut chip, "rs")?),
+                Some(line.line_handle(&mut chip, "rw")?),
+            ),
+            None => {
+                offsets.push(pins.rs.line(&mut chip)?.offset());
+                (None, None)
+            }
+        };
+
+        let values = vec![1; offsets.len()];
+        let mut data = DataLines {
+            lines: chip.get_lines(&offsets)?,
+            handle: None,
+            inputs: false,
+        };
+        data.handle(false, &values)?;
+
+        Ok(GpioBus {
+            rs,
+            rw,
+            enables: vec![pins.enable.line_handle(&mut chip, "enable")?],
+            controller: 0,
+            data,
+            values,
+            mode,
+            reading: false,
+        })
+    }
+
+    /// Requests `enable`, from the chip of the other lines, as the ENABLE line of the second
+    /// controller of 40x4 displays (E2).
+    pub fn with_second_enable(mut self, enable: GpioLine) -> Result<GpioBus, Error> {
+        self.enable_index(enable)?;
+        Ok(self)
+    }
+
+    /// Returns the index of the ENABLE line `enable`, requesting it from the chip of the other
+    /// lines if it is not requested yet.
+    pub(crate) fn enable_index(&mut self, enable: GpioLine) -> Result<u8, Error> {
+        let mut chip = self.enables[0].line().chip();
+        let offset = enable.line(&mut chip)?.offset();
+        if let Some(index) = self.enables.iter().position(|e| e.line().offset() == offset) {
+            return Ok(index as u8);
+        }
+
+        self.enables.push(enable.line_handle(&mut chip, "enable")?);
+        Ok((self.enables.len() - 1) as u8)
+    }
+
+    /// Sets the data lines `lines` (0 being d0) to the bits of `value` and writes them, together
+    /// with RS if it is requested with the data lines, with a single call.
+    fn write_data(&mut self, value: u8, lines: std::ops::Range<usize>) -> Result<(), Error> {
+        let first = match self.mode {
+            Mode::Bits8 => 0,
+            Mode::Bits4 => 4,
+        };
+        for i in lines.filter(|i| *i >= first) {
+            self.values[i - first] = (value >> i) & 0x01;
+        }
+        Ok(self.data.handle(false, &self.values)?.set_values(&self.values)?)
+    }
+}
+
+/// Data lines (and RS when RW is not connected), requested as outputs or inputs
+///
+/// The kernel does not allow changing the direction of requested lines, so they are released
+/// and requested again when the direction changes. The direction changes only when needed: the
+/// lines stay inputs between busy flag polls and become outputs again on the next write. If a
+/// request fails, the next use of the lines requests them again.
+struct DataLines {
+    lines: Lines,
+    handle: Option<MultiLineHandle>,
+    inputs: bool,
+}
+
+impl DataLines {
+
+    /// Returns the handle of the lines requested as `inputs` or outputs set to `values`
+    fn handle(&mut self, inputs: bool, values: &[u8]) -> Result<&MultiLineHandle, Error> {
+        let handle = match self.handle.take() {
+            Some(handle) if self.inputs == inputs => handle,
+            old => {
+                drop(old);
+                let flags = if inputs {
+                    LineRequestFlags::INPUT
+                } else {
+                    LineRequestFlags::OUTPUT
+                };
+                let handle = self.lines.request(flags, values, "data")?;
+                self.inputs = inputs;
+                handle
+            }
+        };
+        Ok(self.handle.insert(handle))
+    }
+}
+
+impl Bus for GpioBus {
+    fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Drives RS, or latches its level for the next data write if RS is requested with the data
+    /// lines.
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        let high = register == Register::Data;
+        match &self.rs {
+            Some(rs) => rs.write(GpioPinSignal::from(high)),
+            None => {
+                if let Some(rs) = self.values.last_mut() {
+                    *rs = high as u8;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        self.enables[self.controller as usize].write(GpioPinSignal::from(high))
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.write_data(nibble << 4, 4..8)
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.write_data(byte, 0..8)
+    }
+
+    fn can_read(&self) -> bool {
+        self.rw.is_some()
+    }
+
+    fn controllers(&self) -> u8 {
+        self.enables.len() as u8
+    }
+
+    fn select_controller(&mut self, index: u8) -> Result<(), Error> {
+        if index >= self.controllers() {
+            return Err(Error::Unsupported("second controller"));
+        }
+        self.controller = index;
+        Ok(())
+    }
+
+    /// Switches the data lines to inputs before the display starts driving them. They are
+    /// switched back to outputs by the next write, after the display stops driving them.
+    fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+        let rw = match &self.rw {
+            Some(rw) => rw,
+            None if read => return Err(Error::Unsupported("read")),
+            None => return Ok(()),
+        };
+        if read == self.reading {
+            return rw.write(GpioPinSignal::from(read));
+        }
+
+        if read {
+            self.data.handle(true, &self.values)?;
+        }
+        rw.write(GpioPinSignal::from(read))?;
+        self.reading = read;
+        Ok(())
+    }
+
+    fn read_data(&mut self) -> Result<u8, Error> {
+        if !self.reading {
+            return Err(Error::Unsupported("read"));
+        }
+
+        let values = self.data.handle(true, &self.values)?.get_values()?;
+        Ok(values.iter().enumerate().fold(0, |value, (i, bit)| value | (bit << i)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pins() -> LinePins {
+        LinePins {
+            chip: GpioChip::Path(PathBuf::from("/nonexistent/gpiochip")),
+            rs: 26.into(),
+            rw: None,
+            enable: 19.into(),
+            data: [None, None, None, None, Some(13.into()), Some(6.into()), Some(5.into()),
+                Some("GPIO11".into())],
+        }
+    }
+
+    #[test]
+    fn validates_line_pins_before_opening_chip() {
+        let mut missing_d7 = pins();
+        missing_d7.data[7] = None;
+        assert!(matches!(GpioBus::with_line_pins(missing_d7), Err(Error::InvalidPinConfig(_))));
+
+        let mut partial_low = pins();
+        partial_low.data[0] = Some(GpioLine::Offset(2));
+        assert!(matches!(GpioBus::with_line_pins(partial_low), Err(Error::InvalidPinConfig(_))));
+
+        assert!(matches!(GpioBus::with_line_pins(pins()), Err(Error::Gpio(_))));
+    }
+
+    #[test]
+    fn converts_line_references() {
+        assert_eq!(GpioLine::from(17), GpioLine::Offset(17));
+        assert_eq!(GpioLine::from("GPIO17"), GpioLine::Name("GPIO17".to_string()));
+        assert_eq!(GpioChip::default(), GpioChip::Number(0));
+    }
+}
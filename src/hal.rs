@@ -0,0 +1,202 @@
+use crate::{Bus, Error, Mode, Register};
+use embedded_hal::digital::v2::OutputPin;
+
+/// Data pins of [HalBus](struct.HalBus.html)
+pub enum DataPins<D> {
+    /// Pins connected to LCD DATA pins d4 to d7
+    Bits4([D; 4]),
+
+    /// Pins connected to LCD DATA pins d0 to d7
+    Bits8([D; 8]),
+}
+
+/// [Bus](trait.Bus.html) implementation for LCD connected to [embedded-hal](
+/// https://docs.rs/embedded-hal) output pins
+///
+/// The bus works without `std` and lets the driver run on microcontrollers. RW must be tied to
+/// ground. Data pins must be of the same type; most HALs provide a "degraded" or "erased" pin
+/// type for this purpose. Pin errors are reported as `Error::Pin`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let bus = HalBus::four_bit(rs, enable, [d4, d5, d6, d7]);
+/// let mut lcd = LCD::with_bus_and_delay(bus, delay);
+/// lcd.begin(16, 2, CharSize::Dots5x8)?;
+/// lcd.print("no_std")?;
+/// ```
+pub struct HalBus<RS, EN, D> {
+    rs: RS,
+    enable: EN,
+    data: DataPins<D>,
+}
+
+impl<RS, EN, D> HalBus<RS, EN, D>
+where
+    RS: OutputPin,
+    EN: OutputPin,
+    D: OutputPin,
+{
+
+    /// Creates the bus for the display wired in 4-bit mode; `data` are the pins connected to
+    /// LCD DATA pins d4 to d7.
+    pub fn four_bit(rs: RS, enable: EN, data: [D; 4]) -> Self {
+        HalBus {
+            rs,
+            enable,
+            data: DataPins::Bits4(data),
+        }
+    }
+
+    /// Creates the bus for the display wired in 8-bit mode; `data` are the pins connected to
+    /// LCD DATA pins d0 to d7.
+    pub fn eight_bit(rs: RS, enable: EN, data: [D; 8]) -> Self {
+        HalBus {
+            rs,
+            enable,
+            data: DataPins::Bits8(data),
+        }
+    }
+
+    /// Consumes the bus and returns the RS, ENABLE and data pins
+    pub fn release(self) -> (RS, EN, DataPins<D>) {
+        (self.rs, self.enable, self.data)
+    }
+}
+
+fn write_pin<P: OutputPin>(pin: &mut P, high: bool) -> Result<(), Error> {
+    if high {
+        pin.set_high().map_err(|_| Error::Pin)
+    } else {
+        pin.set_low().map_err(|_| Error::Pin)
+    }
+}
+
+fn write_pins<P: OutputPin>(pins: &mut [P], value: u8) -> Result<(), Error> {
+    for (i, pin) in pins.iter_mut().enumerate() {
+        write_pin(pin, value & (1 << i) != 0)?;
+    }
+    Ok(())
+}
+
+impl<RS, EN, D> Bus for HalBus<RS, EN, D>
+where
+    RS: OutputPin,
+    EN: OutputPin,
+    D: OutputPin,
+{
+    fn mode(&self) -> Mode {
+        match self.data {
+            DataPins::Bits4(_) => Mode::Bits4,
+            DataPins::Bits8(_) => Mode::Bits8,
+        }
+    }
+
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        write_pin(&mut self.rs, register == Register::Data)
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        write_pin(&mut self.enable, high)
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        match &mut self.data {
+            DataPins::Bits4(pins) => write_pins(pins, nibble),
+            DataPins::Bits8(pins) => write_pins(&mut pins[4..], nibble),
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        match &mut self.data {
+            DataPins::Bits4(_) => Err(Error::Unsupported("8-bit mode")),
+            DataPins::Bits8(pins) => write_pins(pins, byte),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::mock::NoDelay;
+    use crate::sim::SimBus;
+    use crate::{CharSize, LCD};
+    use core::convert::Infallible;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Copy)]
+    enum Line {
+        Rs,
+        Enable,
+        Data(u8),
+    }
+
+    /// Output pin wired to a simulated display
+    struct FakePin {
+        line: Line,
+        display: Rc<RefCell<SimBus>>,
+        data: Rc<RefCell<u8>>,
+    }
+
+    impl OutputPin for FakePin {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Infallible> {
+            self.set(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Infallible> {
+            self.set(true);
+            Ok(())
+        }
+    }
+
+    impl FakePin {
+        fn set(&mut self, high: bool) {
+            let mut display = self.display.borrow_mut();
+            match self.line {
+                Line::Rs if high => display.set_rs(Register::Data).unwrap(),
+                Line::Rs => display.set_rs(Register::Instruction).unwrap(),
+                Line::Enable => display.set_enable(high).unwrap(),
+                Line::Data(bit) => {
+                    let mut data = self.data.borrow_mut();
+                    *data = if high { *data | (1 << bit) } else { *data & !(1 << bit) };
+                    match display.mode() {
+                        Mode::Bits4 => display.write_nibble(*data).unwrap(),
+                        Mode::Bits8 => display.write_byte(*data).unwrap(),
+                    }
+                }
+            }
+        }
+    }
+
+    fn pins<const N: usize>(display: &Rc<RefCell<SimBus>>) -> (FakePin, FakePin, [FakePin; N]) {
+        let data = Rc::new(RefCell::new(0));
+        let pin = |line| FakePin {
+            line,
+            display: Rc::clone(display),
+            data: Rc::clone(&data),
+        };
+        (pin(Line::Rs), pin(Line::Enable), core::array::from_fn(|i| pin(Line::Data(i as u8))))
+    }
+
+    #[test]
+    fn drives_display_through_output_pins() {
+        let display = Rc::new(RefCell::new(SimBus::new(Mode::Bits4, 16, 2)));
+        let (rs, enable, data) = pins(&display);
+        let mut lcd = LCD::with_bus_and_delay(HalBus::four_bit(rs, enable, data), NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.print("no_std").unwrap();
+        assert_eq!(display.borrow().screen()[0], "no_std          ");
+
+        let display = Rc::new(RefCell::new(SimBus::new(Mode::Bits8, 16, 2)));
+        let (rs, enable, data) = pins(&display);
+        let mut lcd = LCD::with_bus_and_delay(HalBus::eight_bit(rs, enable, data), NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.set_cursor(2, 1).unwrap();
+        lcd.print("8-bit").unwrap();
+        assert_eq!(display.borrow().screen()[1], "  8-bit         ");
+    }
+}
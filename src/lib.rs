@@ -3,6 +3,23 @@
 //! works with in either 4- or 8-bit mode (i.e. using 4 or 8 data lines in addition to the `rs`,
 //! `enable`, and, optionally, the `rw` control lines).
 //!
+//! The LCD is driven through a [Bus](trait.Bus.html). [GpioBus](struct.GpioBus.html) talks to the
+//! display connected directly to Raspberry Pi GPIO pins, [Pcf8574Bus](struct.Pcf8574Bus.html) to
+//! the display connected through a PCF8574 I2C backpack and [Mcp230xxBus](struct.Mcp230xxBus.html)
+//! to the one connected through an MCP23008 or MCP23017 I2C expander, and
+//! [ShiftRegisterBus](struct.ShiftRegisterBus.html) to the one connected through a 74HC595 shift
+//! register. Displays sharing their data lines are driven through a
+//! [SharedBus](struct.SharedBus.html); other wirings can be supported by implementing the trait
+//! and passing the bus to [LCD::with_bus()](struct.LCD.html#method.with_bus).
+//!
+//! The HD44780 logic does not depend on the standard library. Without the default `std` feature
+//! the crate is `no_std` and drives the display through [HalBus](struct.HalBus.html), built on
+//! [embedded-hal](https://docs.rs/embedded-hal) `OutputPin` and `DelayUs` traits; the Raspberry Pi
+//! buses, [mock](mock/index.html) and [sim](sim/index.html) modules require `std`.
+//!
+//! Diagnostics are emitted through the [log](https://docs.rs/log) facade: bytes sent over the bus
+//! at `trace` level, commands at `debug` level and busy flag timeouts at `warn` level.
+//!
 //! The crate is a Rust port of [LiquidCrystal](https://github.com/arduino-libraries/LiquidCrystal)
 //! Arduino library. The library API documentation has also been copied and adapted accordingly.
 //!
@@ -12,20 +29,21 @@
 //! has been connected to the LCD display using the referenced below GPIO pins.
 //!
 //! ```rust,no_run
-//! use gpio_cdev::errors;
-//! use rpi_lcd::{CharSize, GpioPin::*, Pins, LCD};
+//! use rpi_lcd::{CharSize, Error, GpioPin::*, Pins, LCD};
 //!
-//! fn do_main() -> Result<(), errors::Error> {
+//! fn do_main() -> Result<(), Error> {
 //!
 //!     let mut lcd = LCD::new(Pins {
 //!         rs: P26,
 //!         rw: None,
 //!         enable: P19,
-//!         data: [NONE, NONE, NONE, NONE, P13, P06, P05, P11],
+//!         data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
 //!     })?;
 //!
-//!     lcd.begin(16, 2, CharSize::Dots5x8);
-//!     lcd.print("Hello,  World!");
+//!     lcd.begin(16, 2, CharSize::Dots5x8)?;
+//!     lcd.print("Hello,  World!")?;
+//!
+//!     Ok(())
 //! }
 //!
 //! fn main() {
@@ -38,86 +56,75 @@
 //! }
 //! ```
 
-use gpio_cdev::*;
-use std::{thread, time};
-use std::convert::TryInto;
-
-fn delay_micros(micros: u64) {
-    thread::sleep(time::Duration::from_micros(micros));
-}
-
-const DATA_PINS: usize = 8;
-
-/// Raspberry Pi GPIO pin references
-///
-/// The values of this enum are used to indicate in [Pins](struct.Pins.html] which GPIO pin is
-/// connected to which LCD pin. Use `GpioPin::NONE` for LCD data pins 0 to 3 to indicate the LCD
-/// works in 4-bit mode.
-#[derive(Debug, PartialEq, Copy, Clone)]
-pub enum GpioPin {
-    NONE = -1,
-    P0 = 0,
-    P1 = 1,
-    P2 = 2,
-    P3 = 3,
-    P4 = 4,
-    P5 = 5,
-    P6 = 6,
-    P7 = 7,
-    P8 = 8,
-    P9 = 9,
-    P10 = 10,
-    P11 = 11,
-    P12 = 12,
-    P13 = 13,
-    P14 = 14,
-    P15 = 15,
-    P16 = 16,
-    P17 = 17,
-    P18 = 18,
-    P19 = 19,
-    P20 = 20,
-    P21 = 21,
-    P22 = 22,
-    P23 = 23,
-    P24 = 24,
-    P25 = 25,
-    P26 = 26,
-    P27 = 27,
-}
-
-impl GpioPin {
-
-    fn line_handle(&self, chip: &mut Chip, consumer: &str) -> Result<LineHandle, errors::Error> {
-        Ok(chip.get_line(*self as u32)?.request(LineRequestFlags::OUTPUT, 1, consumer)?)
-    }
-}
-
-trait OutputPin {
-    fn write(&self, value: GpioPinSignal);
-}
-
-impl OutputPin for LineHandle {
-    fn write(&self, value: GpioPinSignal) {
-        self.set_value(value as u8).unwrap();
-    }
-}
-
-#[derive(Debug)]
-enum GpioPinSignal {
-    High = 0x01,
-    Low = 0x00,
-}
-
-impl GpioPinSignal {
-    fn from(value: u8) -> Self {
-        match value {
-            0 => GpioPinSignal::Low,
-            1 => GpioPinSignal::High,
-            _ => panic!("Invalid signal value: {:?}", value),
-        }
-    }
-}
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// the tests of the no_std core collect their results into std collections
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+use cgram::Cgram;
+use embedded_hal::blocking::delay::DelayUs;
+use frame::Frame;
+use glyphs::Registry;
+use log::{debug, trace, warn};
+
+mod bus;
+mod cgram;
+mod charset;
+#[cfg(feature = "std")]
+mod delay;
+mod error;
+mod expander;
+mod frame;
+mod geometry;
+pub mod glyphs;
+#[cfg(feature = "std")]
+mod gpio;
+mod hal;
+#[cfg(feature = "std")]
+mod mcp230xx;
+#[cfg(feature = "std")]
+pub mod mock;
+#[cfg(feature = "std")]
+mod pcf8574;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod shift_register;
+#[cfg(feature = "std")]
+pub mod sim;
+mod timing;
+
+pub use bus::{Bus, Register};
+pub use charset::{Charset, Encoded, Fallback, Rom};
+#[cfg(feature = "std")]
+pub use delay::StdDelay;
+pub use error::Error;
+pub use expander::BitLayout;
+pub use geometry::Geometry;
+pub use glyphs::{Glyph, GlyphSource};
+#[cfg(feature = "std")]
+pub use gpio::{GpioBus, GpioChip, GpioLine, GpioPin, LinePins, Pins};
+pub use hal::{DataPins, HalBus};
+#[cfg(feature = "std")]
+pub use mcp230xx::{Buttons, Expander, Mcp230xxBus};
+#[cfg(feature = "std")]
+pub use pcf8574::Pcf8574Bus;
+#[cfg(feature = "std")]
+pub use shared::{SharedBus, SharedBusHandle};
+#[cfg(feature = "std")]
+pub use shift_register::{GpioShifter, ShiftOut, ShiftRegisterBus};
+pub use timing::Timing;
+
+/// Interval between two reads of the busy flag
+const BUSY_POLL_MICROS: u32 = 10;
+
+/// Time the address counter takes to update after the busy flag clears (datasheet: 4 µs)
+const ADDRESS_UPDATE_MICROS: u32 = 4;
+
+/// Time after which the controller is considered unresponsive if its busy flag is still set
+const BUSY_TIMEOUT_MICROS: u32 = 10_000;
 
 #[derive(Debug)]
 enum Command {
@@ -161,7 +168,6 @@ impl Command {
     }
 
     fn set_cgram_address(address: u8) -> u8 {
-        eprintln!("address: {:08b}", address);
         Command::SetCGRamAddress as u8 | address
     }
 }
@@ -199,6 +205,7 @@ enum BlinkState {
 #[derive(Debug)]
 enum MoveControl {
     Display = 0x08,
+    #[allow(dead_code)]
     Cursor = 0x00,
 }
 
@@ -234,47 +241,39 @@ pub enum CharSize {
     Dots5x10 = 0x04,
 }
 
+/// Width of the data bus between the Raspberry Pi and the LCD
 #[derive(Debug, PartialEq, Clone, Copy)]
-enum Mode {
+pub enum Mode {
+    /// Only data lines d4 to d7 are connected
     Bits4 = 0x00,
+
+    /// All data lines d0 to d7 are connected
     Bits8 = 0x10,
 }
 
+/// Line wrapping applied by [print()](struct.LCD.html#method.print)
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Wrap {
+    /// Text is written as is: past the last column it continues at the next display memory
+    /// address, which may not be visible or belong to another row; the default
+    #[default]
+    None,
+
+    /// Text continues at the beginning of the next row after the last column, and of the first
+    /// row after the last row
+    Char,
+
+    /// Same as `Char`, but words that do not fit in the rest of the row start on the next row;
+    /// spaces at the wrap are dropped
+    Word,
+}
+
 #[derive(Debug, Clone, Copy)]
 enum Lines {
     Lines1 = 0x00,
     Lines2 = 0x08,
 }
 
-/// LCD pins
-///
-/// To inidicate that LCD works in 4-bit mode use `GpioPin::NONE` as the first
-/// 4 items of `data` pins array.
-#[derive(Debug)]
-pub struct Pins {
-    /// GPIO pin connected to LCD RS pin
-    pub rs: GpioPin,
-
-    /// GPIO pin connected to LCD RW pin; may be set to `None` if LCD RW pin is not used
-    pub rw: Option<GpioPin>,
-
-    /// GPIO pin connected to LCD ENABLE pin
-    pub enable: GpioPin,
-
-    /// GPIO pins connected to LCD DATA pins d0 to d7
-    ///
-    /// Set the first 4 items of this array to `GpioPin::NONE` to indicate LCD
-    /// is working in 4-bit mode.
-    pub data: [GpioPin; DATA_PINS],
-}
-
-struct LineHandles {
-    rs: LineHandle,
-    rw: Option<LineHandle>,
-    enable: LineHandle,
-    data: [Option<LineHandle>; DATA_PINS],
-}
-
 #[derive(Debug)]
 struct DisplayFunction {
     mode: Mode,
@@ -289,23 +288,40 @@ struct DisplayControl {
     blink: BlinkState,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 struct DisplayMode {
     entry_mode: DisplayEntryMode,
     entry_shift_mode: DisplayEntryShiftMode,
 }
 
 /// LCD display main struct
-pub struct LCD {
-    pins: LineHandles,
+///
+/// The display is driven through the bus `B`, and waits for the controller with the delay `D`
+/// ([StdDelay](enum.StdDelay.html) on Raspberry Pi, the HAL delay on microcontrollers).
+pub struct LCD<B: Bus, D: DelayUs<u32>> {
+    bus: B,
+    delay: D,
+    timing: Timing,
     display_function: DisplayFunction,
     display_control: DisplayControl,
     display_mode: DisplayMode,
-    row_offsets: [u8; 4],
-    num_lines: u8,
+    geometry: Geometry,
+    controller: u8,
+    addresses: [u8; 2],
+    wrap: Wrap,
+    charset: Charset,
+    glyphs: Option<GlyphSource>,
+    named_glyphs: Registry,
+    cgram: Cgram,
+    frame: Frame,
+    buffered: bool,
+    device_addresses: Option<[u8; 2]>,
+    busy_polling: bool,
+    shifted: bool,
 }
 
-impl LCD {
+#[cfg(feature = "std")]
+impl LCD<GpioBus, StdDelay> {
 
     /// Creates a variable of type LCD. The display can be controlled using 4 or 8 data
     /// lines. If the former, set the `Pins.data` 0 to 3 array items to `GpioPin::NONE`
@@ -313,26 +329,54 @@ impl LCD {
     /// a pin on the Raspberry; if so, set the `Pins.rw` to `None`. See [Pins](struct.Pins.html)
     /// for detailed parameters description.
     ///
+    /// When RW is connected, the LCD polls the controller busy flag instead of waiting for the
+    /// worst-case execution time of each instruction, which makes screen updates much faster.
+    ///
     /// # Examples
     /// ```rust,no_run
+    /// # use rpi_lcd::{GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
     /// let mut lcd = LCD::new(Pins {
     ///     rs: P26,
     ///     rw: None,
     ///     enable: P19,
-    ///     data: [NONE, NONE, NONE, NONE, P13, P06, P05, P11],
+    ///     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
     /// })?;
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn new(pins: Pins) -> Result<LCD, errors::Error> {
-        let mut display_function = DisplayFunction {
-            mode: Mode::Bits4,
+    pub fn new(pins: Pins) -> Result<LCD<GpioBus, StdDelay>, Error> {
+        Ok(LCD::with_bus(GpioBus::new(pins)?))
+    }
+
+    /// Creates a variable of type LCD connected to lines of any GPIO chip, given by offset or by
+    /// name. See [LinePins](struct.LinePins.html) for detailed parameters description.
+    pub fn with_line_pins(pins: LinePins) -> Result<LCD<GpioBus, StdDelay>, Error> {
+        Ok(LCD::with_bus(GpioBus::with_line_pins(pins)?))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: Bus> LCD<B, StdDelay> {
+
+    /// Creates a variable of type LCD that talks to the display through `bus`. The data bus
+    /// width (4 or 8 bits) is taken from the bus.
+    pub fn with_bus(bus: B) -> LCD<B, StdDelay> {
+        LCD::with_bus_and_delay(bus, StdDelay::default())
+    }
+}
+
+impl<B: Bus, D: DelayUs<u32>> LCD<B, D> {
+
+    /// Creates a variable of type LCD that talks to the display through `bus` and waits for the
+    /// controller using `delay`. This is the constructor to use without `std`.
+    pub fn with_bus_and_delay(bus: B, delay: D) -> LCD<B, D> {
+        let display_function = DisplayFunction {
+            mode: bus.mode(),
             lines: Lines::Lines1,
             char_size: CharSize::Dots5x8,
         };
 
-        if pins.data[0] != GpioPin::NONE {
-            display_function.mode = Mode::Bits8;
-        }
-
         let display_control = DisplayControl {
             display: DisplayState::On,
             cursor: CursorState::Off,
@@ -344,30 +388,95 @@ impl LCD {
             entry_shift_mode: DisplayEntryShiftMode::Decrement,
         };
 
-        let mut chip = Chip::new("/dev/gpiochip0")?;
-
-        let mut data_pins: [Option<LineHandle>; DATA_PINS] = Default::default();
-        for i in 0..DATA_PINS {
-            if pins.data[i] != GpioPin::NONE {
-                let line = pins.data[i].line_handle(&mut chip, format!("data{}", i).as_str()).unwrap();
-                data_pins[i] = Some(line);
-            }
-        }
-        let pins = LineHandles {
-            rs: pins.rs.line_handle(&mut chip, "rs")?,
-            rw: pins.rw.map(|p| { p.line_handle(&mut chip, "rw").unwrap() }),
-            enable: pins.enable.line_handle(&mut chip, "enable")?,
-            data: data_pins,
-        };
-
-        Ok(LCD {
-            pins,
+        LCD {
+            bus,
+            delay,
+            timing: Timing::default(),
             display_function,
             display_control,
             display_mode,
-            row_offsets: [0x00; 4],
-            num_lines: 1,
-        })
+            geometry: Geometry::default(),
+            controller: 0,
+            addresses: [0; 2],
+            wrap: Wrap::default(),
+            charset: Charset::default(),
+            glyphs: None,
+            named_glyphs: Registry::new(),
+            cgram: Cgram::new(cgram::SLOTS as u8),
+            frame: Frame::new(),
+            buffered: false,
+            device_addresses: None,
+            busy_polling: false,
+            shifted: false,
+        }
+    }
+
+    /// Sets the timing of the protocol, [Timing::HD44780](struct.Timing.html) by default
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use rpi_lcd::{GpioPin::*, Pins, Timing, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// let mut lcd = LCD::new(Pins {
+    ///     rs: P26,
+    ///     rw: None,
+    ///     enable: P19,
+    ///     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
+    /// })?
+    /// .with_timing(Timing::SLOW);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Returns the timing of the protocol
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    /// Replaces the delay the LCD waits for the controller with, e.g. to select a
+    /// [StdDelay](enum.StdDelay.html) strategy
+    pub fn with_delay<E: DelayUs<u32>>(self, delay: E) -> LCD<B, E> {
+        LCD {
+            bus: self.bus,
+            delay,
+            timing: self.timing,
+            display_function: self.display_function,
+            display_control: self.display_control,
+            display_mode: self.display_mode,
+            geometry: self.geometry,
+            controller: self.controller,
+            addresses: self.addresses,
+            wrap: self.wrap,
+            charset: self.charset,
+            glyphs: self.glyphs,
+            named_glyphs: self.named_glyphs,
+            cgram: self.cgram,
+            frame: self.frame,
+            buffered: self.buffered,
+            device_addresses: self.device_addresses,
+            busy_polling: self.busy_polling,
+            shifted: self.shifted,
+        }
+    }
+
+    /// Returns a reference to the bus the LCD talks through
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    /// Returns a mutable reference to the bus the LCD talks through
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    /// Consumes the LCD and returns the bus it was talking through
+    pub fn release(self) -> B {
+        self.bus
     }
 
     /// Initializes the interface to the LCD screen, and specifies the dimensions (width and
@@ -375,41 +484,141 @@ impl LCD {
     /// `cols` is the number of characters per line, `lines` is the number of lines,
     /// `char_size` is the size of the character matrix.
     ///
+    /// The usual memory map of the controller is assumed (see
+    /// [Geometry::standard()](struct.Geometry.html#method.standard)); use
+    /// [begin_with_geometry()](#method.begin_with_geometry) for other displays. Fails with
+    /// `Error::InvalidGeometry` if `cols` or `lines` is 0, `lines` is above 4, or the characters
+    /// do not fit in the display memory.
+    ///
+    /// 40x4 displays are driven through two controllers, which requires a bus with a second
+    /// ENABLE line (see [GpioBus::with_second_enable()](
+    /// struct.GpioBus.html#method.with_second_enable)). Both controllers are initialized and the
+    /// LCD methods address the one driving the current row, so that the display is used as a
+    /// single 40x4 screen.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use rpi_lcd::{CharSize, GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::new(Pins {
+    /// #     rs: P26,
+    /// #     rw: None,
+    /// #     enable: P19,
+    /// #     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
+    /// # })?;
+    /// #
+    /// lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn begin(&mut self, cols: u8, lines: u8, char_size: CharSize) -> Result<(), Error> {
+        self.begin_with_geometry(Geometry::standard(cols, lines)?, char_size)
+    }
+
+    /// Same as [begin()](#method.begin) for the display described by `geometry`
+    ///
+    /// Fails with `Error::InvalidGeometry` if `geometry` is not valid or has more controllers
+    /// than the bus has ENABLE lines.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
+    /// # use rpi_lcd::{CharSize, Geometry, GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
     /// # let mut lcd = LCD::new(Pins {
     /// #     rs: P26,
     /// #     rw: None,
     /// #     enable: P19,
-    /// #     data: [NONE, NONE, NONE, NONE, P13, P06, P05, P11],
+    /// #     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
     /// # })?;
     /// #
-    /// lcd.begin(16, 2, CharSize::Dots5x8);
+    /// lcd.begin_with_geometry(Geometry::LCD16X1_TYPE1, CharSize::Dots5x8)?;
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn begin(&mut self, cols: u8, lines: u8, char_size: CharSize) {
-        if lines > 1 {
-            self.display_function.lines = Lines::Lines2;
+    pub fn begin_with_geometry(
+        &mut self,
+        geometry: Geometry,
+        char_size: CharSize,
+    ) -> Result<(), Error> {
+        geometry.validate()?;
+        if geometry.controllers() > self.bus.controllers() {
+            return Err(Error::InvalidGeometry {
+                cols: geometry.cols,
+                lines: geometry.rows,
+            });
         }
 
-        self.num_lines = lines;
+        debug!("begin {:?} {:?}", geometry, char_size);
+
+        // the busy flag cannot be checked before the interface width is set
+        self.busy_polling = false;
 
-        self.set_row_offsets(0x00, 0x40, 0x00 + cols, 0x40 + cols);
+        self.geometry = geometry;
+        self.display_function.lines = if geometry.two_lines() {
+            Lines::Lines2
+        } else {
+            Lines::Lines1
+        };
+
+        // 5x10 dots font is only available in 1-line mode
+        let font_5x10 = char_size != CharSize::Dots5x8 && !geometry.two_lines();
+        self.display_function.char_size = if font_5x10 {
+            CharSize::Dots5x10
+        } else {
+            CharSize::Dots5x8
+        };
 
-        if char_size != CharSize::Dots5x8 && lines == 1 {
-            self.display_function.char_size = CharSize::Dots5x10;
+        // the 5x10 dots font has half as many custom characters
+        let slots = if font_5x10 { 4 } else { cgram::SLOTS as u8 };
+        if self.cgram.slots() != slots {
+            self.cgram = Cgram::new(slots);
         }
 
         // SEE PAGE 45/46 FOR INITIALIZATION SPECIFICATION!
         // according to datasheet, we need at least 40ms after power rises above 2.7V
-        // before sending commands. Arduino can turn on way before 4.5V so we'll wait 50
-        // TODO: Is the wait time for RPi different from Arduino?
-        delay_micros(50000);
-        self.pins.rs.write(GpioPinSignal::Low);
-        self.pins.enable.write(GpioPinSignal::Low);
-        if let Some(rw_pin) = &self.pins.rw {
-            rw_pin.write(GpioPinSignal::Low);
+        // before sending commands; the actual wait is given by the timing profile
+        self.delay.delay_us(self.timing.power_on_delay);
+        for controller in 0..geometry.controllers() {
+            self.select(controller)?;
+            self.initialize_controller()?;
         }
+        self.select(0)?;
+        self.busy_polling = self.bus.can_read();
+
+        // turn the display on with no cursor or blinking default
+        self.display_control.display = DisplayState::On;
+        self.display_control.cursor = CursorState::Off;
+        self.display_control.blink = BlinkState::Off;
+        self.display()?;
+
+        // clear it off
+        self.device_addresses = None;
+        self.clear_display()?;
+
+        // Initialize to default text direction (for romance languages)
+        self.display_mode.entry_mode = DisplayEntryMode::Left;
+        self.display_mode.entry_shift_mode = DisplayEntryShiftMode::Decrement;
+
+        // set the entry mode
+        self.broadcast(Command::entry_mode_set(&self.display_mode))?;
+
+        // the custom characters are lost when the display is powered off
+        for slot in 0..self.cgram.slots() {
+            if let Some(pattern) = self.cgram.pattern(slot) {
+                self.load_char(slot, &pattern)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the reset sequence and sets the interface width, number of lines and font of the
+    /// selected controller
+    fn initialize_controller(&mut self) -> Result<(), Error> {
+        self.bus.set_rs(Register::Instruction)?;
+        self.bus.set_enable(false)?;
+        self.bus.set_rw(false)?;
 
         // put the LCD into 4 bit or 8 bit mode
         if self.display_function.mode == Mode::Bits4 {
@@ -417,53 +626,38 @@ impl LCD {
             // figure 24, pg 46
 
             // we start in 8bit mode, try to set 4 bit mode
-            self.write_4_bits(0x03);
-            delay_micros(45000);
+            self.write_4_bits(0x03)?;
+            self.delay.delay_us(self.timing.reset_delay); // wait min 4.1ms
 
             // second try
-            self.write_4_bits(0x03);
-            delay_micros(4500); // wait min 4.1ms
+            self.write_4_bits(0x03)?;
+            self.delay.delay_us(self.timing.reset_short_delay);
 
             // third go!
-            self.write_4_bits(0x03);
-            delay_micros(150);
+            self.write_4_bits(0x03)?;
+            self.delay.delay_us(self.timing.execution_time);
 
             // finally, set to 4-bit interface
-            self.write_4_bits(0x02);
+            self.write_4_bits(0x02)?;
+            self.delay.delay_us(self.timing.execution_time);
         } else {
             // this is according to the hitachi HD44780 datasheet
             // page 45 figure 23
 
             // Send function set command sequence
-            self.command(Command::function_set(&self.display_function));
-            delay_micros(4500);
+            self.command(Command::function_set(&self.display_function))?;
+            self.delay.delay_us(self.timing.reset_delay);
 
             // second try
-            self.command(Command::function_set(&self.display_function));
-            delay_micros(150);
+            self.command(Command::function_set(&self.display_function))?;
+            self.delay.delay_us(self.timing.reset_short_delay);
 
             // third go
-            self.command(Command::function_set(&self.display_function));
+            self.command(Command::function_set(&self.display_function))?;
         }
 
         // finally, set # lines, font size, etc.
-        self.command(Command::function_set(&self.display_function));
-
-        // turn the display on with no cursor or blinking default
-        self.display_control.display = DisplayState::On;
-        self.display_control.cursor = CursorState::Off;
-        self.display_control.blink = BlinkState::Off;
-        self.display();
-
-        // clear it off
-        self.clear();
-
-        // Initialize to default text direction (for romance languages)
-        self.display_mode.entry_mode = DisplayEntryMode::Left;
-        self.display_mode.entry_shift_mode = DisplayEntryShiftMode::Decrement;
-
-        // set the entry mode
-        self.command(Command::entry_mode_set(&self.display_mode));
+        self.command(Command::function_set(&self.display_function))
     }
 
     /// Position the LCD cursor
@@ -477,110 +671,607 @@ impl LCD {
     /// To position the cursor at the first column of the second line:
     ///
     /// ```rust,no_run
+    /// # use rpi_lcd::{CharSize, GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
     /// # let mut lcd = LCD::new(Pins {
     /// #     rs: P26,
     /// #     rw: None,
     /// #     enable: P19,
-    /// #     data: [NONE, NONE, NONE, NONE, P13, P06, P05, P11],
+    /// #     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
     /// # })?;
     /// #
-    /// # lcd.begin(16, 2, CharSize::Dots5x8);
-    /// lcd.set_cursor(0, 1);
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.set_cursor(0, 1)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_cursor(&mut self, col: u8, row: u8) -> Result<(), Error> {
+        debug!("set cursor to {},{}", col, row);
+
+        let (controller, address) = self.ddram_address(col, row)?;
+        self.activate(controller)?;
+        if self.buffered {
+            self.save_device_addresses();
+        } else {
+            self.command(Command::set_ddram_address(address))?;
+        }
+        self.addresses[controller as usize] = address;
+        Ok(())
+    }
+
+    /// Returns the column and row the next character is written to, or `None` if the cursor
+    /// has moved past the characters shown on the display
+    ///
+    /// The position is tracked in software from the cursor moves and writes, so it is available
+    /// without the RW line. It does not account for the display shift.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{mock::MockBus, CharSize, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+    /// lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.set_cursor(3, 1)?;
+    /// lcd.print("abc")?;
+    /// assert_eq!(lcd.cursor_position(), Some((6, 1)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cursor_position(&self) -> Option<(u8, u8)> {
+        self.geometry
+            .position(self.controller, self.addresses[self.controller as usize])
+    }
+
+    /// Sets the line wrapping applied by [print()](#method.print), `Wrap::None` by default
+    pub fn set_wrap(&mut self, wrap: Wrap) {
+        self.wrap = wrap;
+    }
+
+    /// Returns the line wrapping applied by [print()](#method.print)
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Sets the translation of the text printed by [print()](#method.print) to the character
+    /// codes of the controller ROM, [Charset::A00](struct.Charset.html) by default
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{mock::MockBus, CharSize, Charset, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// # lcd.bus_mut().clear();
+    /// lcd.set_charset(Charset::A02);
+    /// lcd.print("22°C")?;
+    /// assert_eq!(lcd.bus().data(), b"22\xb0C");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_charset(&mut self, charset: Charset) {
+        self.charset = charset;
+    }
+
+    /// Returns the translation of the text printed by [print()](#method.print)
+    pub fn charset(&self) -> Charset {
+        self.charset
+    }
+
+    /// Sets the glyphs [print()](#method.print) loads into the custom characters for the
+    /// characters the ROM does not have. Disabled (`None`) by default.
+    ///
+    /// A glyph takes one of the 8 custom character slots. Slots holding glyphs that are not on
+    /// screen any more are reused, least recently used first; slots written with
+    /// [create_char()](#method.create_char) are left alone. When all slots are in use, the
+    /// character is replaced according to the [Charset](struct.Charset.html) fallback.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{glyphs, sim::SimBus, CharSize, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(SimBus::new(Mode::Bits4, 16, 2));
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.set_glyphs(Some(glyphs::builtin));
+    /// lcd.print("Łódź")?;
+    ///
+    /// // Ł, ó and ź are shown with custom characters 0, 1 and 2
+    /// let codes: Vec<u8> = (0..4).map(|a| lcd.bus().controller().ddram(a)).collect();
+    /// assert_eq!(codes, [0, 1, b'd', 2]);
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn set_cursor(&self, col: u8, row: u8) {
-        eprintln!("Settings cursor to: {},{}", col, row);
+    pub fn set_glyphs(&mut self, glyphs: Option<GlyphSource>) {
+        self.glyphs = glyphs;
+    }
 
-        let mut row = row;
-        let max_rows = self.row_offsets.len().try_into().unwrap();
+    /// Returns the glyphs loaded by [print()](#method.print)
+    pub fn glyphs(&self) -> Option<GlyphSource> {
+        self.glyphs
+    }
 
-        if row >= max_rows {
-            row = max_rows - 1;
+    /// Registers `glyph` under `name`, replacing the glyph registered under the same name
+    ///
+    /// The glyph is printed by [print()](#method.print) for `{name}`, or written with the code
+    /// returned by [glyph_code()](#method.glyph_code). It is loaded into a custom character the
+    /// first time it is shown and the slot is reused like the ones of
+    /// [set_glyphs()](#method.set_glyphs). Glyphs not drawn for the font of the display are
+    /// printed as the fallback character. Up to 32 glyphs can be registered. Fails with
+    /// `Error::InvalidGlyph` if there are too many glyphs or if the name is empty, longer than
+    /// 16 bytes or contains braces or whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{sim::SimBus, CharSize, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(SimBus::new(Mode::Bits4, 16, 2));
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.register_glyph("smiley", [0x00, 0x0a, 0x00, 0x00, 0x11, 0x0e, 0x00, 0x00])?;
+    /// lcd.print("hi {smiley}")?;
+    /// assert_eq!(lcd.bus().controller().ddram(3), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_glyph(&mut self, name: &str, glyph: impl Into<Glyph>) -> Result<(), Error> {
+        let glyph = glyph.into();
+        debug!("register glyph {:?} {:02x?}", name, glyph.rows());
+        self.named_glyphs.insert(name, glyph)
+    }
+
+    /// Registers the glyphs of `text`, written in the
+    /// [glyph file format](glyphs/index.html#glyph-files), under their names
+    ///
+    /// Fails with `Error::GlyphFormat` if the text cannot be parsed; the glyphs before the error
+    /// are registered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{glyphs, mock::MockBus, CharSize, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.register_glyphs(glyphs::ICONS)?;
+    /// lcd.print("{lock} {check}")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_glyphs(&mut self, text: &str) -> Result<(), Error> {
+        for glyph in glyphs::parse(text) {
+            let (name, glyph) = glyph?;
+            self.register_glyph(name, glyph)?;
         }
+        Ok(())
+    }
 
-        if row >= self.num_lines {
-            row = self.num_lines - 1;
+    /// Returns the character code of the glyph registered under `name`, loading it into a
+    /// custom character if needed
+    ///
+    /// Fails with `Error::InvalidGlyph` if no glyph is registered under `name`, if the glyph is
+    /// not drawn for the font of the display or if all the custom characters are on screen.
+    pub fn glyph_code(&mut self, name: &str) -> Result<u8, Error> {
+        let glyph = self
+            .named_glyphs
+            .get(name)
+            .ok_or(Error::InvalidGlyph("no glyph registered under the name"))?;
+        if glyph.char_size() != self.display_function.char_size {
+            return Err(Error::InvalidGlyph("glyph height does not match the font"));
         }
+        self.load_glyph(glyph)?
+            .ok_or(Error::InvalidGlyph("all custom characters are on screen"))
+    }
 
-        self.command(Command::set_ddram_address(col + self.row_offsets[row as usize]));
+    /// Turns the buffered mode on or off
+    ///
+    /// In buffered mode [print()](#method.print), [write()](#method.write),
+    /// [set_cursor()](#method.set_cursor) and [clear()](#method.clear) only draw into an
+    /// in-memory copy of the screen, and [flush()](#method.flush) sends the characters that
+    /// changed since the previous flush. Redrawing the whole screen then neither flickers nor
+    /// rewrites the characters that stay the same. Leaving the buffered mode flushes the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{mock::MockBus, CharSize, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+    /// lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.set_buffered(true)?;
+    /// for temperature in &[21, 22] {
+    ///     lcd.clear()?;
+    ///     lcd.print(&format!("Temp: {} C", temperature))?;
+    ///     lcd.flush()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_buffered(&mut self, buffered: bool) -> Result<(), Error> {
+        if self.buffered && !buffered {
+            self.flush()?;
+        }
+        self.buffered = buffered;
+        Ok(())
+    }
+
+    /// Returns `true` if the LCD is in buffered mode
+    pub fn is_buffered(&self) -> bool {
+        self.buffered
+    }
+
+    /// Sends the characters drawn in buffered mode that differ from the ones on the display,
+    /// then moves the cursor to the drawing position
+    ///
+    /// The display memory address is only set when the changed characters are not consecutive.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        let increment = self.increments();
+        let active = self.controller;
+        let device = self.device_addresses.take().unwrap_or(self.addresses);
+        let mut next: [Option<u8>; 2] = [None; 2];
+
+        // writes must not shift the display
+        let shift = matches!(self.display_mode.entry_shift_mode, DisplayEntryShiftMode::Increment);
+        let no_shift = DisplayMode {
+            entry_shift_mode: DisplayEntryShiftMode::Decrement,
+            ..self.display_mode
+        };
+        if shift && self.frame.is_dirty() {
+            self.broadcast(Command::entry_mode_set(&no_shift))?;
+        }
+
+        for row in 0..self.geometry.rows {
+            for col in 0..self.geometry.cols {
+                let index = self.cell(col, row);
+                if !self.frame.is_changed(index) {
+                    continue;
+                }
+
+                let (controller, address) = self.ddram_address(col, row)?;
+                self.select(controller)?;
+                if next[controller as usize] != Some(address) {
+                    self.command(Command::set_ddram_address(address))?;
+                }
+                self.write_data(self.frame.get(index))?;
+                self.frame.mark_shown(index);
+                next[controller as usize] = Some(self.geometry.next_address(address, increment));
+            }
+        }
+
+        if shift && next.iter().any(Option::is_some) {
+            self.broadcast(Command::entry_mode_set(&self.display_mode))?;
+        }
+        for controller in 0..self.geometry.controllers() {
+            let address = self.addresses[controller as usize];
+            if next[controller as usize].unwrap_or(device[controller as usize]) != address {
+                self.select(controller)?;
+                self.command(Command::set_ddram_address(address))?;
+            }
+        }
+        self.select(active)
+    }
+
+    /// Remembers the addresses of the controllers before the drawing position moves away from
+    /// them in buffered mode
+    fn save_device_addresses(&mut self) {
+        if self.device_addresses.is_none() {
+            self.device_addresses = Some(self.addresses);
+        }
+    }
+
+    /// Returns the index of the character at `col` and `row` in the frame
+    fn cell(&self, col: u8, row: u8) -> usize {
+        row as usize * self.geometry.cols as usize + col as usize
+    }
+
+    /// Returns the controller driving `row` and the address of the character at `col` in its
+    /// display memory
+    fn ddram_address(&self, col: u8, row: u8) -> Result<(u8, u8), Error> {
+        let address = self
+            .geometry
+            .address(col, row)
+            .ok_or(Error::InvalidPosition { col, row })?;
+        Ok((self.geometry.controller(row), address))
     }
 
     /// Print text to the LCD
     ///
+    /// The characters are translated to the codes of the controller ROM according to
+    /// [set_charset()](#method.set_charset); `'\u{0}'` to `'\u{7}'` stand for the custom
+    /// characters. The text is wrapped according to [set_wrap()](#method.set_wrap). When
+    /// wrapping, a line feed (`'\n'`) moves the cursor to the beginning of the next row.
+    /// `{name}` prints the glyph registered with [register_glyph()](#method.register_glyph);
+    /// braces around any other text are printed as they are.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
+    /// # use rpi_lcd::{CharSize, GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
     /// # let mut lcd = LCD::new(Pins {
     /// #     rs: P26,
     /// #     rw: None,
     /// #     enable: P19,
-    /// #     data: [NONE, NONE, NONE, NONE, P13, P06, P05, P11],
+    /// #     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
     /// # })?;
     /// #
-    /// # lcd.begin(16, 2, CharSize::Dots5x8);
-    /// lcd.print("Hello,  World!");
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.print("Hello,  World!")?;
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn print(&self, msg: &str) {
-        eprintln!("Printing: {}", msg);
+    pub fn print(&mut self, msg: &str) -> Result<(), Error> {
+        debug!("print {:?}", msg);
 
-        msg.as_bytes().iter().for_each(|b| {
-            self.write(*b);
-        });
+        match self.wrap {
+            Wrap::None | Wrap::Char => self.print_text(msg).map(|_| ()),
+            Wrap::Word => self.print_words(msg),
+        }
+    }
+
+    fn print_words(&mut self, msg: &str) -> Result<(), Error> {
+        let mut wrapped = false;
+        for piece in msg.split_inclusive([' ', '\n']) {
+            let word = piece.strip_suffix([' ', '\n']).unwrap_or(piece);
+            let separator = &piece[word.len()..];
+
+            if !word.is_empty() {
+                let cols = self.geometry.cols as usize;
+                let remaining = self.remaining_cols();
+                let length = self.text_len(word);
+                if let Some((_, row)) = self.cursor_position() {
+                    if length > remaining && remaining < cols && length <= cols {
+                        self.next_row(row)?;
+                    }
+                }
+                wrapped = self.print_text(word)?;
+            }
+
+            match separator {
+                " " if wrapped => {}
+                "" => {}
+                _ => wrapped = self.print_text(separator)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the ROM codes of the characters and named glyphs of `text`. When wrapping, moves
+    /// to the next row after the last column and for a line feed. Returns `true` if the cursor
+    /// moved to the next row after the last character.
+    fn print_text(&mut self, text: &str) -> Result<bool, Error> {
+        let mut wrapped = false;
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if c == '\n' && self.wrap != Wrap::None {
+                rest = &rest[1..];
+                wrapped = match self.cursor_position() {
+                    Some((_, row)) => self.next_row(row).map(|_| true)?,
+                    None => false,
+                };
+                continue;
+            }
+
+            let (codes, length) = match self.named_glyphs.escape(rest) {
+                Some((glyph, length)) => (self.encode_glyph(glyph)?, length),
+                None => (self.encode(c)?, c.len_utf8()),
+            };
+            rest = &rest[length..];
+            for b in codes {
+                wrapped = match self.wrap {
+                    Wrap::None => self.write(b).map(|_| false)?,
+                    Wrap::Char | Wrap::Word => self.write_wrapped(b)?,
+                };
+            }
+        }
+        Ok(wrapped)
+    }
+
+    /// Returns the number of ROM codes `text` is printed with
+    fn text_len(&self, text: &str) -> usize {
+        let mut length = 0;
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match self.named_glyphs.escape(rest) {
+                Some((_, escape)) => {
+                    length += 1;
+                    rest = &rest[escape..];
+                }
+                None => {
+                    length += self.encoded_len(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        length
+    }
+
+    /// Returns the ROM codes `c` is printed with, loading its glyph into a custom character if
+    /// the ROM does not have it
+    fn encode(&mut self, c: char) -> Result<Encoded, Error> {
+        if self.charset.rom.code(c).is_none() {
+            if let Some(glyph) = self.glyphs.and_then(|glyphs| glyphs(c)) {
+                if let Some(code) = self.load_glyph(glyph.into())? {
+                    return Ok(Encoded::code(code));
+                }
+            }
+        }
+        Ok(self.charset.encode(c))
+    }
+
+    /// Returns the ROM code `glyph` is printed with: its custom character, or the fallback if no
+    /// slot is free
+    fn encode_glyph(&mut self, glyph: Glyph) -> Result<Encoded, Error> {
+        let code = self.load_glyph(glyph)?.unwrap_or(self.charset.substitute());
+        Ok(Encoded::code(code))
+    }
+
+    /// Returns the number of ROM codes `c` is printed with
+    fn encoded_len(&self, c: char) -> usize {
+        let glyph = self.glyphs.and_then(|glyphs| glyphs(c));
+        let font_5x8 = self.display_function.char_size == CharSize::Dots5x8;
+        if self.charset.rom.code(c).is_none() && glyph.is_some() && font_5x8 {
+            1
+        } else {
+            self.charset.encode(c).count()
+        }
+    }
+
+    /// Returns the character code of the custom character showing `glyph`, loading it into the
+    /// least recently used slot that is not on screen if needed, or `None` if there is no such
+    /// slot or if the glyph is not drawn for the font of the display
+    fn load_glyph(&mut self, glyph: Glyph) -> Result<Option<u8>, Error> {
+        if glyph.char_size() != self.display_function.char_size {
+            debug!("glyph {:02x?} does not match the font", glyph.rows());
+            return Ok(None);
+        }
+        if let Some(slot) = self.cgram.find(&glyph) {
+            return Ok(Some(self.cgram.code(slot)));
+        }
+
+        // the frame does not follow the display shift, which can bring any loaded glyph into view
+        let cells = self.geometry.cols as usize * self.geometry.rows as usize;
+        let (cgram, frame, shifted) = (&self.cgram, &self.frame, self.shifted);
+        let on_screen = |slot| {
+            shifted && cgram.pattern(slot).is_some()
+                || frame.shows(cells, |code| cgram.slot(code) == Some(slot))
+        };
+        let slot = match cgram.victim(on_screen) {
+            Some(slot) => slot,
+            None => {
+                debug!("no custom character left for {:02x?}", glyph.rows());
+                return Ok(None);
+            }
+        };
+        self.load_char(slot, &glyph)?;
+        self.cgram.load(slot, glyph);
+        Ok(Some(self.cgram.code(slot)))
+    }
+
+    /// Writes `value` and moves to the beginning of the next row after the last column, or to
+    /// the other half of rows split in two (see [Geometry::split](struct.Geometry.html)).
+    /// Returns `true` if the cursor moved to the next row.
+    fn write_wrapped(&mut self, value: u8) -> Result<bool, Error> {
+        let position = self.cursor_position();
+        self.write(value)?;
+        match position {
+            Some((col, row)) if col == self.last_col() => self.next_row(row).map(|_| true),
+            Some((col, row)) => {
+                let col = if self.increments() { col + 1 } else { col - 1 };
+                if self.cursor_position() != Some((col, row)) {
+                    self.set_cursor(col, row)?;
+                }
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Moves the cursor to the beginning of the row after `row`, in the text direction
+    fn next_row(&mut self, row: u8) -> Result<(), Error> {
+        let row = (row + 1) % self.geometry.rows;
+        let col = if self.increments() {
+            0
+        } else {
+            self.geometry.cols - 1
+        };
+        self.set_cursor(col, row)
+    }
+
+    /// Returns the number of characters that fit between the cursor and the end of the row
+    fn remaining_cols(&self) -> usize {
+        match self.cursor_position() {
+            Some((col, _)) if self.increments() => (self.geometry.cols - col) as usize,
+            Some((col, _)) => col as usize + 1,
+            None => self.geometry.cols as usize,
+        }
+    }
+
+    /// Returns the column the text reaches last on each row
+    fn last_col(&self) -> u8 {
+        if self.increments() {
+            self.geometry.cols - 1
+        } else {
+            0
+        }
+    }
+
+    fn increments(&self) -> bool {
+        matches!(self.display_mode.entry_mode, DisplayEntryMode::Left)
     }
 
     /// Clear the LCD screen and position the cursor in the upper-left corner
-    pub fn clear(&self) {
-        self.command(Command::clear_display());
-        delay_micros(2000);
+    pub fn clear(&mut self) -> Result<(), Error> {
+        if !self.buffered {
+            return self.clear_display();
+        }
+
+        self.save_device_addresses();
+        self.frame.clear(false);
+        self.addresses = [0; 2];
+        self.activate(0)
+    }
+
+    fn clear_display(&mut self) -> Result<(), Error> {
+        self.broadcast_with_delay(Command::clear_display(), self.timing.clear_home_time)?;
+        self.frame.clear(true);
+        self.addresses = [0; 2];
+        self.shifted = false;
+        self.activate(0)
     }
 
     /// Position the cursor in the upper-left of the LCD
     ///
     /// That is, use that location in outputting subsequent text to the display. To also clear the
     /// display, use the [clear()](#method.clear) function instead.
-    pub fn home(&self) {
-        self.command(Command::return_home());
-        delay_micros(2000);
+    pub fn home(&mut self) -> Result<(), Error> {
+        self.broadcast_with_delay(Command::return_home(), self.timing.clear_home_time)?;
+        self.addresses = [0; 2];
+        self.device_addresses = None;
+        self.shifted = false;
+        self.activate(0)
     }
 
     /// Turn off the LCD display, without losing the text currently shown on it
     ///
     /// See also [display()](#method.display).
-    pub fn no_display(&mut self) {
+    pub fn no_display(&mut self) -> Result<(), Error> {
         self.display_control.display = DisplayState::Off;
-        self.command(Command::display_control(&self.display_control));
+        self.update_display_control()
     }
 
     /// Turn on the LCD display, after it's been turned off with [no_display()](#method.no_display)
     ///
     /// This will restore the text (and cursor) that was on the display.
-    pub fn display(&mut self) {
+    pub fn display(&mut self) -> Result<(), Error> {
         self.display_control.display = DisplayState::On;
-        self.command(Command::display_control(&self.display_control));
+        self.update_display_control()
     }
 
     /// Hide the LCD cursor
     ///
     /// See also [cursor](#method.cursor).
-    pub fn no_cursor(&mut self) {
+    pub fn no_cursor(&mut self) -> Result<(), Error> {
         self.display_control.cursor = CursorState::Off;
-        self.command(Command::display_control(&self.display_control));
+        self.update_display_control()
     }
 
     /// Display the LCD cursor: an underscore (line) at the position to which the next character
     /// will be written
     ///
     /// See also [no_cursor](#method.no_cursor).
-    pub fn cursor(&mut self) {
+    pub fn cursor(&mut self) -> Result<(), Error> {
         self.display_control.cursor = CursorState::On;
-        self.command(Command::display_control(&self.display_control));
+        self.update_display_control()
     }
 
     /// Turn off the blinking LCD cursor
     ///
     /// See also [blink()](#method.blink).
-    pub fn no_blink(&mut self) {
+    pub fn no_blink(&mut self) -> Result<(), Error> {
         self.display_control.blink = BlinkState::Off;
-        self.command(Command::display_control(&self.display_control));
+        self.update_display_control()
     }
 
     /// Display the blinking LCD cursor
@@ -589,23 +1280,25 @@ impl LCD {
     /// result will depend on the particular display.
     ///
     /// See also [no_blink()](#method.no_blink).
-    pub fn blink(&mut self) {
+    pub fn blink(&mut self) -> Result<(), Error> {
         self.display_control.blink = BlinkState::On;
-        self.command(Command::display_control(&self.display_control));
+        self.update_display_control()
     }
 
     /// Scroll the contents of the display (text and cursor) one space to the left
     ///
     /// See also [scroll_display_right()](#method.scroll_display_right).
-    pub fn scroll_display_left(&self) {
-        self.command(Command::cursor_shift(&MoveControl::Display, &MoveDirection::Left));
+    pub fn scroll_display_left(&mut self) -> Result<(), Error> {
+        self.shifted = true;
+        self.broadcast(Command::cursor_shift(&MoveControl::Display, &MoveDirection::Left))
     }
 
     /// Scroll the contents of the display (text and cursor) one space to the right
     ///
     /// See also [scroll_display_left](#method.scroll_display_left).
-    pub fn scroll_display_right(&self) {
-        self.command(Command::cursor_shift(&MoveControl::Display, &MoveDirection::Right));
+    pub fn scroll_display_right(&mut self) -> Result<(), Error> {
+        self.shifted = true;
+        self.broadcast(Command::cursor_shift(&MoveControl::Display, &MoveDirection::Right))
     }
 
     /// Set the direction for text written to the LCD to left-to-right, the default
@@ -614,9 +1307,9 @@ impl LCD {
     /// but does not affect previously-output text.
     ///
     /// See also [right_to_left()](#method.right_to_left).
-    pub fn left_to_right(&mut self) {
+    pub fn left_to_right(&mut self) -> Result<(), Error> {
         self.display_mode.entry_mode = DisplayEntryMode::Left;
-        self.command(Command::entry_mode_set(&self.display_mode));
+        self.broadcast(Command::entry_mode_set(&self.display_mode))
     }
 
     /// Set the direction for text written to the LCD to right-to-left (the default is
@@ -626,9 +1319,9 @@ impl LCD {
     /// but does not affect previously-output text.
     ///
     /// See also [left-to-right()](#method.left_to_right).
-    pub fn right_to_left(&mut self) {
+    pub fn right_to_left(&mut self) -> Result<(), Error> {
         self.display_mode.entry_mode = DisplayEntryMode::Right;
-        self.command(Command::entry_mode_set(&self.display_mode));
+        self.broadcast(Command::entry_mode_set(&self.display_mode))
     }
 
     /// Turn on automatic scrolling of the LCD
@@ -639,17 +1332,34 @@ impl LCD {
     /// has the effect of outputting each new character to the same location on the LCD.
     ///
     /// See also [no_autscroll()](#method.no_autscroll).
-    pub fn autoscroll(&mut self) {
+    pub fn autoscroll(&mut self) -> Result<(), Error> {
         self.display_mode.entry_shift_mode = DisplayEntryShiftMode::Increment;
-        self.command(Command::entry_mode_set(&self.display_mode));
+        self.broadcast(Command::entry_mode_set(&self.display_mode))
     }
 
     /// Turn off automatic scrolling of the LCD
     ///
     /// See also [autoscroll()](#method.autoscroll).
-    pub fn no_autscroll(&mut self) {
+    pub fn no_autscroll(&mut self) -> Result<(), Error> {
         self.display_mode.entry_shift_mode = DisplayEntryShiftMode::Decrement;
-        self.command(Command::entry_mode_set(&self.display_mode));
+        self.broadcast(Command::entry_mode_set(&self.display_mode))
+    }
+
+    /// Turn on the LCD backlight
+    ///
+    /// Only available if the bus controls the backlight, e.g. [Pcf8574Bus](struct.Pcf8574Bus.html).
+    /// See also [no_backlight()](#method.no_backlight).
+    pub fn backlight(&mut self) -> Result<(), Error> {
+        debug!("backlight on");
+        self.bus.set_backlight(true)
+    }
+
+    /// Turn off the LCD backlight
+    ///
+    /// See also [backlight()](#method.backlight).
+    pub fn no_backlight(&mut self) -> Result<(), Error> {
+        debug!("backlight off");
+        self.bus.set_backlight(false)
     }
 
     /// Create a custom character (glyph) for use on the LCD
@@ -657,19 +1367,24 @@ impl LCD {
     /// Up to eight characters of 5x8 pixels are supported (numbered 0 to 7). The appearance of
     /// each custom character is specified by an array of eight bytes, one for each row. The five
     /// least significant bits of each byte determine the pixels in that row. To display a custom
-    /// character on the screen, [write()](#method.write) its number.
+    /// character on the screen, [write()](#method.write) its number. The cursor position is
+    /// preserved. Fails with `Error::InvalidGlyph` if `location` is greater than 7 or if the
+    /// display uses the 5x10 dots font, whose custom characters are created with
+    /// [create_glyph()](#method.create_glyph).
     ///
     /// # Examples
     ///
     /// ```rust,no_run
+    /// # use rpi_lcd::{CharSize, GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
     /// # let mut lcd = LCD::new(Pins {
     /// #     rs: P26,
     /// #     rw: None,
     /// #     enable: P19,
-    /// #     data: [NONE, NONE, NONE, NONE, P13, P06, P05, P11],
+    /// #     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
     /// # })?;
     /// #
-    /// # lcd.begin(16, 2, CharSize::Dots5x8);
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
     /// #
     ///
     /// let smiley = [
@@ -692,78 +1407,899 @@ impl LCD {
     ///     0b00000u8,
     ///     0b00000u8,
     /// ];
-    /// lcd.create_char(0, smiley);
-    /// lcd.create_char(1, big_dot);
-    /// lcd.write(0);
-    /// lcd.set_cursor(3, 1);
-    /// lcd.write(1);
+    /// lcd.create_char(0, smiley)?;
+    /// lcd.create_char(1, big_dot)?;
+    /// lcd.write(0)?;
+    /// lcd.set_cursor(3, 1)?;
+    /// lcd.write(1)?;
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn create_char(&self, location: u8, charmap: [u8; 8]) {
-        let location = location & 0x7;
-        self.command(Command::set_cgram_address(location << 3));
-        charmap.iter().for_each(|b| {
-            eprintln!("{:05b}", *b);
-            self.write(*b);
-        });
+    pub fn create_char(&mut self, location: u8, charmap: [u8; 8]) -> Result<(), Error> {
+        self.create_glyph(location, charmap)
+    }
+
+    /// Create a custom character of either font
+    ///
+    /// With the 5x8 dots font there are 8 custom characters (numbered 0 to 7) of 8 rows, shown
+    /// by writing their number. With the 5x10 dots font there are 4 custom characters (numbered
+    /// 0 to 3) of 11 rows, shown by writing twice their number. The cursor position is
+    /// preserved. Fails with `Error::InvalidGlyph` if the glyph is not drawn for the font of the
+    /// display or if there is no such custom character.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use rpi_lcd::{mock::MockBus, CharSize, Mode, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+    /// lcd.begin(16, 1, CharSize::Dots5x10)?;
+    /// let arrow = [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x15, 0x0e, 0x04, 0x00];
+    /// lcd.create_glyph(1, arrow)?;
+    /// lcd.write(2)?;
+    ///
+    /// assert!(lcd.create_glyph(4, arrow).is_err());
+    /// assert!(lcd.create_glyph(0, [0x00; 8]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_glyph(&mut self, location: u8, glyph: impl Into<Glyph>) -> Result<(), Error> {
+        let glyph = glyph.into();
+        debug!("create glyph {} {:02x?}", location, glyph.rows());
+
+        if glyph.char_size() != self.display_function.char_size {
+            return Err(Error::InvalidGlyph("glyph height does not match the font"));
+        }
+        if location >= self.cgram.slots() {
+            return Err(Error::InvalidGlyph("no such custom character"));
+        }
+
+        self.load_char(location, &glyph)?;
+        self.cgram.reserve(location, glyph);
+        Ok(())
+    }
+
+    /// Writes `glyph` to custom character `slot` of every controller
+    fn load_char(&mut self, slot: u8, glyph: &Glyph) -> Result<(), Error> {
+        let address = self.cgram.address(slot);
+        let active = self.controller;
+        for controller in 0..self.geometry.controllers() {
+            self.select(controller)?;
+            self.command(Command::set_cgram_address(address))?;
+            for b in glyph.rows() {
+                self.write_data(*b)?;
+            }
+            self.command(Command::set_ddram_address(self.addresses[controller as usize]))?;
+        }
+        self.device_addresses = None;
+
+        self.select(active)
+    }
+
+    /// Read the character code stored in the display memory at `col` and `row`
+    ///
+    /// The cursor position is preserved. Fails with `Error::Unsupported` if the RW line is not
+    /// wired.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use rpi_lcd::{CharSize, GpioPin::*, Pins, LCD};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// # let mut lcd = LCD::new(Pins {
+    /// #     rs: P26,
+    /// #     rw: Some(P20),
+    /// #     enable: P19,
+    /// #     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
+    /// # })?;
+    /// # lcd.begin(16, 2, CharSize::Dots5x8)?;
+    /// lcd.print("Hello")?;
+    /// assert_eq!(lcd.read_char_at(1, 0)?, b'e');
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_char_at(&mut self, col: u8, row: u8) -> Result<u8, Error> {
+        let (controller, address) = self.ddram_address(col, row)?;
+        let active = self.controller;
+
+        self.select(controller)?;
+        let value = self.read_ddram(address);
+        self.select(active)?;
+
+        value
+    }
+
+    /// Reads the character at `address` of the selected controller, preserving its address
+    /// counter
+    fn read_ddram(&mut self, address: u8) -> Result<u8, Error> {
+        let cursor = self.address_counter()?;
+
+        self.command(Command::set_ddram_address(address))?;
+        let value = self.read_data()?;
+        self.command(Command::set_ddram_address(cursor))?;
+
+        Ok(value)
+    }
+
+    /// Read the glyph of custom character `location`: 0 to 7 of 8 rows with the 5x8 dots font,
+    /// 0 to 3 of 11 rows with the 5x10 dots font
+    ///
+    /// The cursor position is preserved. Fails with `Error::InvalidGlyph` if there is no such
+    /// custom character and with `Error::Unsupported` if the RW line is not wired.
+    pub fn read_cgram(&mut self, location: u8) -> Result<Glyph, Error> {
+        if location >= self.cgram.slots() {
+            return Err(Error::InvalidGlyph("no such custom character"));
+        }
+        let cursor = self.address_counter()?;
+
+        let height = match self.display_function.char_size {
+            CharSize::Dots5x8 => 8,
+            CharSize::Dots5x10 => 11,
+        };
+        self.command(Command::set_cgram_address(self.cgram.address(location)))?;
+        let mut rows = [0u8; 11];
+        self.reading_forward(|lcd| {
+            for row in rows[..height].iter_mut() {
+                *row = lcd.read_data()? & 0x1f;
+            }
+            Ok(())
+        })?;
+        self.command(Command::set_ddram_address(cursor))?;
+
+        Glyph::new(&rows[..height])
+    }
+
+    /// Read the address counter, i.e. the display memory address the next character is written
+    /// to
+    ///
+    /// Fails with `Error::Unsupported` if the RW line is not wired.
+    pub fn address_counter(&mut self) -> Result<u8, Error> {
+        if !self.bus.can_read() {
+            return Err(Error::Unsupported("read"));
+        }
+
+        self.bus.set_rs(Register::Instruction)?;
+        self.bus.set_rw(true)?;
+        let status = self.read_8_bits();
+        self.bus.set_rw(false)?;
+
+        Ok(status? & 0x7f)
+    }
+
+    /// Read the characters currently stored in the visible display memory, one row per line
+    ///
+    /// The cursor position is preserved. Fails with `Error::Unsupported` if the RW line is not
+    /// wired.
+    #[cfg(feature = "std")]
+    pub fn read_screen(&mut self) -> Result<Vec<Vec<u8>>, Error> {
+        let active = self.controller;
+        let mut cursors = [0u8; 2];
+        for controller in 0..self.geometry.controllers() {
+            self.select(controller)?;
+            cursors[controller as usize] = self.address_counter()?;
+        }
+
+        let screen = self.reading_forward(|lcd| {
+            let mut screen = Vec::with_capacity(lcd.geometry.rows as usize);
+            for row in 0..lcd.geometry.rows {
+                let mut line = Vec::with_capacity(lcd.geometry.cols as usize);
+                let mut next = None;
+                for col in 0..lcd.geometry.cols {
+                    let (controller, address) = lcd.ddram_address(col, row)?;
+                    if next != Some((controller, address)) {
+                        lcd.select(controller)?;
+                        lcd.command(Command::set_ddram_address(address))?;
+                    }
+                    line.push(lcd.read_data()?);
+                    next = Some((controller, address + 1));
+                }
+                screen.push(line);
+            }
+            Ok(screen)
+        })?;
+
+        for controller in 0..self.geometry.controllers() {
+            self.select(controller)?;
+            self.command(Command::set_ddram_address(cursors[controller as usize]))?;
+        }
+        self.select(active)?;
+
+        Ok(screen)
+    }
+
+    /// Runs `read` with the controllers moving the address counter forward after each read, as
+    /// in left to right mode, then restores the entry mode
+    fn reading_forward<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        if self.increments() {
+            return read(self);
+        }
+
+        let forward = DisplayMode {
+            entry_mode: DisplayEntryMode::Left,
+            ..self.display_mode
+        };
+        self.broadcast(Command::entry_mode_set(&forward))?;
+        let value = read(self);
+        self.broadcast(Command::entry_mode_set(&self.display_mode))?;
+        value
+    }
+
+    /// Write a character to the LCD, or to the buffer in buffered mode (see
+    /// [set_buffered()](#method.set_buffered))
+    pub fn write(&mut self, value: u8) -> Result<(), Error> {
+        if self.buffered {
+            self.save_device_addresses();
+        } else {
+            self.write_data(value)?;
+            let shift = self.display_mode.entry_shift_mode;
+            self.shifted |= matches!(shift, DisplayEntryShiftMode::Increment);
+        }
+        if let Some((col, row)) = self.cursor_position() {
+            let index = self.cell(col, row);
+            self.frame.set(index, value, !self.buffered);
+        }
+
+        let controller = self.controller as usize;
+        let address = self.addresses[controller];
+        self.addresses[controller] = self.geometry.next_address(address, self.increments());
+        Ok(())
+    }
+
+    fn write_data(&mut self, value: u8) -> Result<(), Error> {
+        self.send(value, Register::Data)?;
+        self.wait_ready(self.timing.execution_time)
     }
 
-    /// Write a character to the LCD
-    pub fn write(&self, value: u8) {
-        self.send(value, GpioPinSignal::High);
+    fn command(&mut self, value: u8) -> Result<(), Error> {
+        self.command_with_delay(value, self.timing.execution_time)
     }
 
-    fn set_row_offsets(&mut self, row1: u8, row2: u8, row3: u8, row4: u8) {
-        self.row_offsets[0] = row1;
-        self.row_offsets[1] = row2;
-        self.row_offsets[2] = row3;
-        self.row_offsets[3] = row4;
+    /// Sends the instruction `value` to every controller of the display
+    fn broadcast(&mut self, value: u8) -> Result<(), Error> {
+        self.broadcast_with_delay(value, self.timing.execution_time)
     }
 
-    fn command(&self, value: u8) {
-        eprintln!("command: {:08b}", value);
-        self.send(value, GpioPinSignal::Low);
+    fn broadcast_with_delay(&mut self, value: u8, micros: u32) -> Result<(), Error> {
+        let active = self.controller;
+        for controller in 0..self.geometry.controllers() {
+            self.select(controller)?;
+            self.command_with_delay(value, micros)?;
+        }
+        self.select(active)
     }
 
-    fn send(&self, value: u8, signal: GpioPinSignal) {
-        self.pins.rs.write(signal);
+    /// Sends the display control to every controller. Only the active controller shows the
+    /// cursor, the others keep it hidden.
+    fn update_display_control(&mut self) -> Result<(), Error> {
+        let active = self.controller;
+        let inactive = DisplayControl {
+            display: self.display_control.display,
+            cursor: CursorState::Off,
+            blink: BlinkState::Off,
+        };
+        for controller in 0..self.geometry.controllers() {
+            self.select(controller)?;
+            let control = if controller == active {
+                &self.display_control
+            } else {
+                &inactive
+            };
+            self.command(Command::display_control(control))?;
+        }
+        self.select(active)
+    }
 
-        if let Some(rw_pin) = &self.pins.rw {
-            rw_pin.write(GpioPinSignal::Low);
+    /// Makes `controller` the one text is written to, moving the cursor over to it if it is
+    /// shown
+    fn activate(&mut self, controller: u8) -> Result<(), Error> {
+        if controller == self.controller {
+            return Ok(());
         }
 
+        self.select(controller)?;
+        let cursor_shown = matches!(self.display_control.cursor, CursorState::On)
+            || matches!(self.display_control.blink, BlinkState::On);
+        if cursor_shown {
+            self.update_display_control()?;
+        }
+        Ok(())
+    }
+
+    /// Selects the controller subsequent transfers go to
+    fn select(&mut self, controller: u8) -> Result<(), Error> {
+        if controller != self.controller {
+            trace!("select controller {}", controller);
+            self.bus.select_controller(controller)?;
+            self.controller = controller;
+        }
+        Ok(())
+    }
+
+    fn command_with_delay(&mut self, value: u8, micros: u32) -> Result<(), Error> {
+        debug!("command {:#010b}", value);
+        self.send(value, Register::Instruction)?;
+        self.wait_ready(micros)
+    }
+
+    /// Waits until the controller is ready for the next transfer: polls the busy flag if the bus
+    /// can read from the display, otherwise sleeps for the instruction execution time `micros`.
+    fn wait_ready(&mut self, micros: u32) -> Result<(), Error> {
+        if !self.busy_polling {
+            self.delay.delay_us(micros);
+            return Ok(());
+        }
+
+        self.bus.set_rs(Register::Instruction)?;
+        self.bus.set_rw(true)?;
+
+        let mut waited = 0;
+        let result = loop {
+            match self.read_8_bits() {
+                Ok(status) if status & 0x80 == 0 => break Ok(()),
+                Ok(_) if waited >= BUSY_TIMEOUT_MICROS => break Err(Error::BusyTimeout),
+                Ok(_) => {
+                    self.delay.delay_us(BUSY_POLL_MICROS);
+                    waited += BUSY_POLL_MICROS;
+                }
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.bus.set_rw(false)?;
+        if result.is_err() {
+            warn!("busy flag still set after {}us", waited);
+        }
+
+        // the address counter is updated shortly after the busy flag clears
+        self.delay.delay_us(ADDRESS_UPDATE_MICROS);
+        result
+    }
+
+    fn read_data(&mut self) -> Result<u8, Error> {
+        if !self.bus.can_read() {
+            return Err(Error::Unsupported("read"));
+        }
+
+        self.bus.set_rs(Register::Data)?;
+        self.bus.set_rw(true)?;
+        let value = self.read_8_bits();
+        self.bus.set_rw(false)?;
+
+        let value = value?;
+        trace!("read {:?} {:#04x}", Register::Data, value);
+        self.wait_ready(self.timing.execution_time)?;
+        Ok(value)
+    }
+
+    fn read_8_bits(&mut self) -> Result<u8, Error> {
+        if self.display_function.mode == Mode::Bits8 {
+            return self.read_pulse();
+        }
+
+        let high = self.read_pulse()?;
+        let low = self.read_pulse()?;
+        Ok((high << 4) | (low & 0x0f))
+    }
+
+    fn read_pulse(&mut self) -> Result<u8, Error> {
+        self.bus.set_enable(true)?;
+        self.delay.delay_us(self.timing.enable_pulse_width);
+        let value = self.bus.read_data();
+        self.bus.set_enable(false)?;
+        self.delay.delay_us(self.timing.enable_pulse_width);
+        value
+    }
+
+    fn send(&mut self, value: u8, register: Register) -> Result<(), Error> {
+        trace!("send {:?} {:#04x}", register, value);
+        self.bus.set_rs(register)?;
+        self.bus.set_rw(false)?;
+
         if self.display_function.mode == Mode::Bits8 {
-            self.write_8_bits(value);
+            self.write_8_bits(value)?;
         } else {
-            self.write_4_bits(value >> 4);
-            self.write_4_bits(value);
+            self.write_4_bits(value >> 4)?;
+            self.write_4_bits(value)?;
         }
+
+        Ok(())
     }
 
-    fn pulse_enable(&self) {
-        self.pins.enable.write(GpioPinSignal::Low);
-        delay_micros(1);
-        self.pins.enable.write(GpioPinSignal::High);
-        delay_micros(1);
-        self.pins.enable.write(GpioPinSignal::Low);
-        delay_micros(100);
+    fn pulse_enable(&mut self) -> Result<(), Error> {
+        self.bus.set_enable(false)?;
+        self.delay.delay_us(self.timing.enable_pulse_width);
+        self.bus.set_enable(true)?;
+        self.delay.delay_us(self.timing.enable_pulse_width);
+        self.bus.set_enable(false)?;
+        self.delay.delay_us(self.timing.enable_pulse_width);
+
+        Ok(())
     }
 
-    fn write_4_bits(&self, value: u8) {
-        self.pins.data[4..8]
-            .iter()
-            .enumerate()
-            .for_each(|(i, pin)| {
-                pin.as_ref().unwrap().write(GpioPinSignal::from((value >> i) & 0x01));
-            });
+    fn write_4_bits(&mut self, value: u8) -> Result<(), Error> {
+        self.bus.write_nibble(value & 0x0f)?;
+        self.pulse_enable()
+    }
+
+    fn write_8_bits(&mut self, value: u8) -> Result<(), Error> {
+        self.bus.write_byte(value)?;
+        self.pulse_enable()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::mock::{MockBus, NoDelay, Pin, Transfer};
+    use crate::sim::SimBus;
+
+    fn lcd(mode: Mode) -> LCD<MockBus, NoDelay> {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(mode), NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.bus_mut().clear();
+        lcd
+    }
+
+    #[test]
+    fn begin_initializes_4_bit_mode() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits4), NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+
+        assert_eq!(
+            lcd.bus().instructions(),
+            vec![0x30, 0x30, 0x30, 0x20, 0x28, 0x0c, 0x01, 0x06]
+        );
+    }
+
+    #[test]
+    fn begin_initializes_8_bit_mode() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits8), NoDelay);
+        lcd.begin(20, 1, CharSize::Dots5x10).unwrap();
+
+        assert_eq!(
+            lcd.bus().instructions(),
+            vec![0x34, 0x34, 0x34, 0x34, 0x0c, 0x01, 0x06]
+        );
+    }
+
+    #[test]
+    fn print_writes_data() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.print("Hello").unwrap();
 
-        self.pulse_enable();
+        assert_eq!(lcd.bus().data(), b"Hello".to_vec());
+        assert!(lcd.bus().instructions().is_empty());
     }
 
-    fn write_8_bits(&self, value: u8) {
-        self.pins.data.iter().enumerate().for_each(|(i, pin)| {
-            pin.as_ref().unwrap().write(GpioPinSignal::from((value >> i) & 0x01));
+    #[test]
+    fn print_translates_to_rom_codes() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.print("25°C 5µs é\u{1}").unwrap();
+        assert_eq!(lcd.bus().data(), b"25\xdfC 5\xe4s e\x01".to_vec());
+
+        lcd.bus_mut().clear();
+        lcd.set_charset(Charset {
+            fallback: Fallback::Substitute(b'#'),
+            ..Charset::A02
         });
+        lcd.print("é→").unwrap();
+        assert_eq!(lcd.bus().data(), b"\xe9#".to_vec());
+    }
+
+    #[test]
+    fn named_glyphs_share_custom_characters() {
+        let mut lcd = lcd(Mode::Bits4);
+        let bell = [0x04, 0x0e, 0x0e, 0x0e, 0x1f, 0x00, 0x04, 0x00];
+        lcd.create_char(3, bell).unwrap();
+        lcd.register_glyph("bell", bell).unwrap();
+        lcd.register_glyph("alarm", bell).unwrap();
+        lcd.bus_mut().clear();
+
+        // identical bitmaps are not uploaded again
+        lcd.print("{bell}{alarm} {x} {bell").unwrap();
+        assert_eq!(lcd.bus().data(), b"\x03\x03 {x} {bell".to_vec());
+        assert_eq!(lcd.glyph_code("alarm").unwrap(), 3);
+        assert!(matches!(lcd.glyph_code("x"), Err(Error::InvalidGlyph(_))));
+    }
+
+    #[test]
+    fn begin_reloads_custom_characters() {
+        let mut lcd = lcd(Mode::Bits8);
+        let bell = [0x04, 0x0e, 0x0e, 0x0e, 0x1f, 0x00, 0x04, 0x00];
+        lcd.register_glyph("bell", bell).unwrap();
+        let code = lcd.glyph_code("bell").unwrap();
+
+        lcd.bus_mut().clear();
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        let transfers = lcd.bus().transfers();
+        let at = transfers
+            .iter()
+            .position(|t| *t == Transfer::Instruction(0x40 | code << 3))
+            .unwrap();
+        assert_eq!(transfers[at + 1..at + 9], bell.map(Transfer::Data));
+        assert_eq!(lcd.glyph_code("bell").unwrap(), code);
+    }
+
+    #[test]
+    fn set_cursor_uses_row_offsets() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits4), NoDelay);
+        lcd.begin(20, 4, CharSize::Dots5x8).unwrap();
+        lcd.bus_mut().clear();
+
+        lcd.set_cursor(0, 0).unwrap();
+        lcd.set_cursor(3, 1).unwrap();
+        lcd.set_cursor(1, 2).unwrap();
+        lcd.set_cursor(19, 3).unwrap();
+
+        assert_eq!(lcd.bus().instructions(), vec![0x80, 0xc3, 0x95, 0xe7]);
+    }
+
+    #[test]
+    fn create_char_writes_cgram() {
+        let mut lcd = lcd(Mode::Bits8);
+        let glyph = [0x00, 0x11, 0x11, 0x00, 0x11, 0x0e, 0x00, 0x00];
+        lcd.create_char(1, glyph).unwrap();
+
+        let mut expected = vec![Transfer::Instruction(0x48)];
+        expected.extend(glyph.iter().map(|b| Transfer::Data(*b)));
+        expected.push(Transfer::Instruction(0x80));
+        assert_eq!(lcd.bus().transfers(), expected);
+
+        lcd.bus_mut().clear();
+        assert!(matches!(lcd.create_char(9, glyph), Err(Error::InvalidGlyph(_))));
+        assert!(lcd.bus().transfers().is_empty());
+    }
+
+    #[test]
+    fn tracks_cursor_position() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits4), NoDelay);
+        lcd.begin(20, 4, CharSize::Dots5x8).unwrap();
+        assert_eq!(lcd.cursor_position(), Some((0, 0)));
+
+        // without wrapping, row 0 continues on row 2
+        lcd.set_cursor(18, 0).unwrap();
+        lcd.print("abc").unwrap();
+        assert_eq!(lcd.cursor_position(), Some((1, 2)));
+
+        lcd.right_to_left().unwrap();
+        lcd.write(b'x').unwrap();
+        assert_eq!(lcd.cursor_position(), Some((0, 2)));
+
+        lcd.left_to_right().unwrap();
+        lcd.set_cursor(19, 3).unwrap();
+        lcd.write(b'x').unwrap();
+        assert_eq!(lcd.cursor_position(), Some((0, 0)));
+
+        lcd.set_cursor(5, 1).unwrap();
+        lcd.create_char(0, [0; 8]).unwrap();
+        assert_eq!(lcd.cursor_position(), Some((5, 1)));
+        lcd.home().unwrap();
+        assert_eq!(lcd.cursor_position(), Some((0, 0)));
+    }
+
+    #[test]
+    fn flush_sends_changed_characters_only() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.set_buffered(true).unwrap();
+        lcd.print("Hello").unwrap();
+        lcd.set_cursor(0, 1).unwrap();
+        lcd.print("World").unwrap();
+        assert!(lcd.bus().transfers().is_empty());
+
+        lcd.flush().unwrap();
+        let mut expected = vec![Transfer::Instruction(0x80)];
+        expected.extend(b"Hello".iter().map(|b| Transfer::Data(*b)));
+        expected.push(Transfer::Instruction(0xc0));
+        expected.extend(b"World".iter().map(|b| Transfer::Data(*b)));
+        assert_eq!(lcd.bus().transfers(), expected);
+
+        // redrawing the same screen only sends the differences
+        lcd.bus_mut().clear();
+        lcd.clear().unwrap();
+        lcd.print("Help").unwrap();
+        lcd.set_cursor(0, 1).unwrap();
+        lcd.print("World").unwrap();
+        lcd.flush().unwrap();
+        assert_eq!(
+            lcd.bus().transfers(),
+            vec![
+                Transfer::Instruction(0x83),
+                Transfer::Data(b'p'),
+                Transfer::Data(b' '),
+                Transfer::Instruction(0xc5),
+            ]
+        );
+
+        lcd.bus_mut().clear();
+        lcd.flush().unwrap();
+        assert!(lcd.bus().transfers().is_empty());
+    }
+
+    #[test]
+    fn flush_moves_cursor_to_drawing_position() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.set_buffered(true).unwrap();
+        lcd.print("ab").unwrap();
+        lcd.set_cursor(4, 1).unwrap();
+        lcd.flush().unwrap();
+
+        assert_eq!(
+            lcd.bus().transfers(),
+            vec![
+                Transfer::Instruction(0x80),
+                Transfer::Data(b'a'),
+                Transfer::Data(b'b'),
+                Transfer::Instruction(0xc4),
+            ]
+        );
+        assert_eq!(lcd.cursor_position(), Some((4, 1)));
+
+        lcd.bus_mut().clear();
+        lcd.set_buffered(false).unwrap();
+        lcd.print("c").unwrap();
+        assert_eq!(lcd.bus().transfers(), vec![Transfer::Data(b'c')]);
+    }
+
+    #[test]
+    fn cursor_position_past_last_column_is_not_shown() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.print("0123456789abcdef").unwrap();
+        assert_eq!(lcd.cursor_position(), None);
+    }
+
+    #[test]
+    fn display_control_commands() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.no_display().unwrap();
+        lcd.display().unwrap();
+        lcd.cursor().unwrap();
+        lcd.blink().unwrap();
+        lcd.no_cursor().unwrap();
+        lcd.no_blink().unwrap();
+
+        assert_eq!(
+            lcd.bus().instructions(),
+            vec![0x08, 0x0c, 0x0e, 0x0f, 0x0d, 0x0c]
+        );
+    }
+
+    #[test]
+    fn clear_and_home_commands() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.clear().unwrap();
+        lcd.home().unwrap();
+
+        assert_eq!(lcd.bus().instructions(), vec![0x01, 0x02]);
+    }
+
+    #[test]
+    fn shift_and_entry_mode_commands() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.scroll_display_left().unwrap();
+        lcd.scroll_display_right().unwrap();
+        lcd.right_to_left().unwrap();
+        lcd.autoscroll().unwrap();
+        lcd.left_to_right().unwrap();
+        lcd.no_autscroll().unwrap();
+
+        assert_eq!(
+            lcd.bus().instructions(),
+            vec![0x18, 0x1c, 0x04, 0x05, 0x07, 0x06]
+        );
+    }
+
+    struct FailingBus;
+
+    impl Bus for FailingBus {
+        fn mode(&self) -> Mode {
+            Mode::Bits4
+        }
+
+        fn set_rs(&mut self, _register: Register) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn set_enable(&mut self, _high: bool) -> Result<(), Error> {
+            Err(Error::Unsupported("enable"))
+        }
+
+        fn write_nibble(&mut self, _nibble: u8) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn bus_errors_are_returned() {
+        let mut lcd = LCD::with_bus_and_delay(FailingBus, NoDelay);
+
+        assert!(matches!(lcd.print("x"), Err(Error::Unsupported("enable"))));
+        assert!(matches!(lcd.clear(), Err(Error::Unsupported("enable"))));
+    }
+
+    /// Delay recording the requested durations instead of waiting
+    #[derive(Default)]
+    struct RecordingDelay(Vec<u32>);
+
+    impl DelayUs<u32> for RecordingDelay {
+        fn delay_us(&mut self, us: u32) {
+            self.0.push(us);
+        }
+    }
+
+    #[test]
+    fn timing_profile_sets_delays() {
+        let bus = MockBus::new(Mode::Bits4);
+        let mut lcd = LCD::with_bus_and_delay(bus, RecordingDelay::default())
+            .with_timing(Timing::SLOW);
+        assert_eq!(lcd.timing(), Timing::SLOW);
+
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        assert_eq!(lcd.delay.0[0], 100_000);
+        assert!(lcd.delay.0.contains(&4500));
+        assert!(lcd.delay.0.contains(&150));
+
+        lcd.delay.0.clear();
+        lcd.clear().unwrap();
+        assert_eq!(lcd.delay.0, vec![10, 10, 10, 10, 10, 10, 5000]);
+
+        lcd.delay.0.clear();
+        lcd.write(b'x').unwrap();
+        assert_eq!(lcd.delay.0.last(), Some(&120));
+    }
+
+    #[test]
+    fn reads_require_rw_line() {
+        let mut lcd = lcd(Mode::Bits4);
+
+        assert!(matches!(lcd.address_counter(), Err(Error::Unsupported("read"))));
+        assert!(matches!(lcd.read_char_at(0, 0), Err(Error::Unsupported("read"))));
+        assert!(matches!(lcd.read_cgram(0), Err(Error::Unsupported("read"))));
+        assert!(lcd.bus().transfers().is_empty());
+    }
+
+    #[test]
+    fn set_cursor_rejects_positions_outside_display() {
+        let mut lcd = lcd(Mode::Bits4);
+
+        assert!(matches!(
+            lcd.set_cursor(0, 2),
+            Err(Error::InvalidPosition { col: 0, row: 2 })
+        ));
+        assert!(matches!(
+            lcd.set_cursor(16, 0),
+            Err(Error::InvalidPosition { col: 16, row: 0 })
+        ));
+        assert!(lcd.bus().transfers().is_empty());
+    }
+
+    #[test]
+    fn begin_with_geometry_uses_its_memory_map() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits8), NoDelay);
+        lcd.begin_with_geometry(Geometry::LCD16X1_TYPE1, CharSize::Dots5x10)
+            .unwrap();
+        assert_eq!(lcd.bus().instructions()[0], 0x38);
+        lcd.bus_mut().clear();
+
+        lcd.set_cursor(7, 0).unwrap();
+        lcd.set_cursor(8, 0).unwrap();
+        assert_eq!(lcd.bus().instructions(), vec![0x87, 0xc0]);
+    }
+
+    #[test]
+    fn begin_rejects_more_than_4_lines() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits4), NoDelay);
+
+        assert!(matches!(
+            lcd.begin(20, 5, CharSize::Dots5x8),
+            Err(Error::InvalidGeometry { cols: 20, lines: 5 })
+        ));
+        assert!(matches!(
+            lcd.begin(40, 4, CharSize::Dots5x8),
+            Err(Error::InvalidGeometry { cols: 40, lines: 4 })
+        ));
+    }
+
+    #[test]
+    fn begin_rejects_empty_geometry() {
+        let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits4), NoDelay);
+
+        assert!(matches!(
+            lcd.begin(16, 0, CharSize::Dots5x8),
+            Err(Error::InvalidGeometry { cols: 16, lines: 0 })
+        ));
+        assert!(lcd.bus().transfers().is_empty());
+    }
+
+    /// Simulated display whose busy flag stays set for the first `busy_reads` reads
+    struct BusyBus {
+        display: SimBus,
+        busy_reads: usize,
+        reads: usize,
+    }
+
+    impl Bus for BusyBus {
+        fn mode(&self) -> Mode {
+            Mode::Bits8
+        }
+
+        fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+            self.display.set_rs(register)
+        }
+
+        fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+            self.display.set_enable(high)
+        }
+
+        fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+            self.display.write_nibble(nibble)
+        }
+
+        fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+            self.display.write_byte(byte)
+        }
+
+        fn can_read(&self) -> bool {
+            true
+        }
+
+        fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+            self.display.set_rw(read)
+        }
+
+        fn read_data(&mut self) -> Result<u8, Error> {
+            self.reads += 1;
+            let value = self.display.read_data()?;
+            if self.busy_reads > 0 {
+                self.busy_reads -= 1;
+                return Ok(value | 0x80);
+            }
+            Ok(value)
+        }
+    }
+
+    fn busy_lcd(busy_reads: usize) -> LCD<BusyBus, NoDelay> {
+        let bus = BusyBus {
+            display: SimBus::new(Mode::Bits8, 16, 2),
+            busy_reads,
+            reads: 0,
+        };
+        LCD::with_bus_and_delay(bus, NoDelay)
+    }
+
+    #[test]
+    fn polls_busy_flag_when_bus_can_read() {
+        let mut lcd = busy_lcd(0);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        let reads = lcd.bus().reads;
+        assert!(reads > 0);
+
+        lcd.bus_mut().busy_reads = 3;
+        lcd.print("ok").unwrap();
+        assert_eq!(lcd.bus().reads, reads + 2 + 3);
+        assert_eq!(lcd.bus().display.screen()[0], "ok              ");
+    }
+
+    #[test]
+    fn busy_flag_times_out() {
+        let mut lcd = busy_lcd(0);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+
+        lcd.bus_mut().busy_reads = usize::MAX;
+        assert!(matches!(lcd.write(b'x'), Err(Error::BusyTimeout)));
+    }
+
+    #[test]
+    fn fixed_delays_are_used_without_read_support() {
+        let mut lcd = lcd(Mode::Bits4);
+        lcd.print("x").unwrap();
 
-        self.pulse_enable();
+        assert!(!lcd.bus().transitions().iter().any(|t| t.pin == Pin::Rw && t.high));
     }
 }
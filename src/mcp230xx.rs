@@ -0,0 +1,572 @@
+use crate::{BitLayout, Bus, Error, Mode, Register};
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+use std::io;
+use std::path::Path;
+
+/// MCP23008 registers
+const MCP23008_IODIR: u8 = 0x00;
+const MCP23008_GPPU: u8 = 0x06;
+const MCP23008_GPIO: u8 = 0x09;
+const MCP23008_OLAT: u8 = 0x0a;
+
+/// MCP23017 registers (IOCON.BANK = 0, port A register followed by port B register)
+const MCP23017_IODIRA: u8 = 0x00;
+const MCP23017_GPPUA: u8 = 0x0c;
+const MCP23017_GPIOA: u8 = 0x12;
+const MCP23017_OLATA: u8 = 0x14;
+const MCP23017_OLATB: u8 = 0x15;
+
+/// RGB LCD shield backlight bits: red=GPA6, green=GPA7, blue=GPB0 (active low)
+const RGB_SHIELD_BACKLIGHT: [u8; 3] = [6, 7, 8];
+
+/// MCP230xx I/O expander model
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Expander {
+    /// 8-bit expander; bits 0 to 7 are GP0 to GP7
+    Mcp23008,
+
+    /// 16-bit expander; bits 0 to 7 are GPA0 to GPA7, bits 8 to 15 are GPB0 to GPB7
+    Mcp23017,
+}
+
+impl Expander {
+
+    fn width(self) -> u8 {
+        match self {
+            Expander::Mcp23008 => 8,
+            Expander::Mcp23017 => 16,
+        }
+    }
+}
+
+/// State of the buttons of the Adafruit RGB LCD shield
+///
+/// The buttons pull their input low when pressed; a field is `true` while its input reads low.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Buttons {
+    /// `true` if the select button (GPA0) is pressed
+    pub select: bool,
+
+    /// `true` if the right button (GPA1) is pressed
+    pub right: bool,
+
+    /// `true` if the down button (GPA2) is pressed
+    pub down: bool,
+
+    /// `true` if the up button (GPA3) is pressed
+    pub up: bool,
+
+    /// `true` if the left button (GPA4) is pressed
+    pub left: bool,
+}
+
+/// [Bus](trait.Bus.html) implementation for LCD connected through an MCP23008 or MCP23017 I2C
+/// I/O expander, as found on the Adafruit I2C/SPI character LCD backpack and RGB LCD shields
+///
+/// Each change of the LCD lines is sent as a single output latch register write. Expander pins
+/// not used by the LCD are configured as inputs with pull-ups and can be read with
+/// [read_inputs()](#method.read_inputs).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{CharSize, Mcp230xxBus, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let mut lcd = LCD::with_bus(Mcp230xxBus::rgb_shield("/dev/i2c-1")?);
+/// lcd.begin(16, 2, CharSize::Dots5x8)?;
+/// lcd.print("Press a button")?;
+///
+/// if lcd.bus_mut().buttons()?.select {
+///     lcd.bus_mut().set_color(false, true, false)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Mcp230xxBus<D> {
+    device: D,
+    expander: Expander,
+    layout: BitLayout,
+    rgb: Option<[u8; 3]>,
+    iodir: u16,
+    port: u16,
+    latched: u16,
+}
+
+impl Mcp230xxBus<LinuxI2CDevice> {
+
+    /// Default address of the expander (A0 to A2 pulled low)
+    pub const ADDRESS: u16 = 0x20;
+
+    /// Opens the `expander` at `address` on the Linux I2C bus device `path` (e.g. `/dev/i2c-1`)
+    /// and configures it for an LCD wired according to `layout`.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        address: u16,
+        expander: Expander,
+        layout: BitLayout,
+    ) -> Result<Self, Error> {
+        let device = LinuxI2CDevice::new(path, address).map_err(|e| Error::I2c(e.into()))?;
+        Mcp230xxBus::new(device, expander, layout)
+    }
+
+    /// Opens the Adafruit I2C/SPI character LCD backpack at `address`
+    pub fn adafruit_backpack<P: AsRef<Path>>(path: P, address: u16) -> Result<Self, Error> {
+        Mcp230xxBus::open(path, address, Expander::Mcp23008, BitLayout::MCP23008_ADAFRUIT)
+    }
+
+    /// Opens the Adafruit RGB LCD shield at its default address
+    pub fn rgb_shield<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let device =
+            LinuxI2CDevice::new(path, Self::ADDRESS).map_err(|e| Error::I2c(e.into()))?;
+        Mcp230xxBus::with_rgb_backlight(
+            device,
+            Expander::Mcp23017,
+            BitLayout::MCP23017_RGB_SHIELD,
+            RGB_SHIELD_BACKLIGHT,
+        )
+    }
+}
+
+impl<D> Mcp230xxBus<D>
+where
+    D: I2CDevice,
+    D::Error: Into<io::Error>,
+{
+
+    /// Creates the bus talking to the `expander` through `device` and configures its pins: bits
+    /// used by `layout` become outputs, the remaining ones inputs with pull-ups.
+    ///
+    /// Fails with `Error::InvalidPinConfig` if `layout` uses a bit the expander does not have
+    /// (above 7 for MCP23008, above 15 for MCP23017) or the same bit for more than one line.
+    pub fn new(device: D, expander: Expander, layout: BitLayout) -> Result<Self, Error> {
+        Mcp230xxBus::init(device, expander, layout, None)
+    }
+
+    /// Same as [new()](#method.new) for displays with an RGB backlight. `rgb` holds the bits of
+    /// the red, green and blue LEDs, which are lit when their bit is low. The backlight is
+    /// switched on (white). The `rgb` bits are checked against the expander and `layout` as
+    /// well.
+    pub fn with_rgb_backlight(
+        device: D,
+        expander: Expander,
+        layout: BitLayout,
+        rgb: [u8; 3],
+    ) -> Result<Self, Error> {
+        Mcp230xxBus::init(device, expander, layout, Some(rgb))
+    }
+
+    fn init(
+        device: D,
+        expander: Expander,
+        layout: BitLayout,
+        rgb: Option<[u8; 3]>,
+    ) -> Result<Self, Error> {
+        let outputs = layout.checked_mask(expander.width(), rgb.as_ref().map_or(&[], |rgb| rgb))?;
+
+        let mut bus = Mcp230xxBus {
+            device,
+            expander,
+            layout,
+            rgb,
+            iodir: !outputs,
+            port: layout.with_backlight(0, true),
+            latched: 0,
+        };
+
+        if let Some(rgb) = rgb {
+            bus.port = rgb.iter().fold(bus.port, |port, bit| layout.set(port, *bit, false));
+        }
+        bus.write_register(MCP23008_GPPU, MCP23017_GPPUA, !outputs)?;
+        bus.write_port()?;
+        bus.write_register(MCP23008_IODIR, MCP23017_IODIRA, bus.iodir)?;
+        Ok(bus)
+    }
+
+    /// Returns the I2C device the bus talks through
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Consumes the bus and returns the I2C device it was talking through
+    pub fn release(self) -> D {
+        self.device
+    }
+
+    /// Reads the levels of the expander pins configured as inputs, i.e. not used by the LCD.
+    /// Other bits are returned as 0.
+    pub fn read_inputs(&mut self) -> Result<u16, Error> {
+        let value = self.read_port()?;
+        Ok(value & self.iodir & !self.layout.data_mask())
+    }
+
+    /// Reads the buttons of the Adafruit RGB LCD shield (select=GPA0, right=GPA1, down=GPA2,
+    /// up=GPA3, left=GPA4, pulled low when pressed)
+    pub fn buttons(&mut self) -> Result<Buttons, Error> {
+        let inputs = self.read_inputs()?;
+        let pressed = |bit: u16| self.iodir & (1 << bit) != 0 && inputs & (1 << bit) == 0;
+
+        Ok(Buttons {
+            select: pressed(0),
+            right: pressed(1),
+            down: pressed(2),
+            up: pressed(3),
+            left: pressed(4),
+        })
+    }
+
+    /// Sets the color of the RGB backlight. Fails with `Error::Unsupported` if the bus was not
+    /// created with an RGB backlight.
+    pub fn set_color(&mut self, red: bool, green: bool, blue: bool) -> Result<(), Error> {
+        let rgb = match self.rgb {
+            Some(rgb) => rgb,
+            None => return Err(Error::Unsupported("RGB backlight")),
+        };
+
+        for (bit, on) in rgb.iter().zip(&[red, green, blue]) {
+            self.port = self.layout.set(self.port, *bit, !on);
+        }
+        self.write_port()
+    }
+
+    fn read_port(&mut self) -> Result<u16, Error> {
+        match self.expander {
+            Expander::Mcp23008 => self.device.smbus_read_byte_data(MCP23008_GPIO).map(u16::from),
+            Expander::Mcp23017 => self.device.smbus_read_word_data(MCP23017_GPIOA),
+        }
+        .map_err(|e| Error::I2c(e.into()))
+    }
+
+    fn write_register(&mut self, mcp23008: u8, mcp23017: u8, value: u16) -> Result<(), Error> {
+        match self.expander {
+            Expander::Mcp23008 => self.device.smbus_write_byte_data(mcp23008, value as u8),
+            Expander::Mcp23017 => self.device.smbus_write_word_data(mcp23017, value),
+        }
+        .map_err(|e| Error::I2c(e.into()))
+    }
+
+    fn write_port(&mut self) -> Result<(), Error> {
+        let changed = self.port ^ self.latched;
+        let result = match self.expander {
+            Expander::Mcp23008 => self
+                .device
+                .smbus_write_byte_data(MCP23008_OLAT, self.port as u8),
+            Expander::Mcp23017 if changed & 0x00ff == 0 => self
+                .device
+                .smbus_write_byte_data(MCP23017_OLATB, (self.port >> 8) as u8),
+            Expander::Mcp23017 if changed & 0xff00 == 0 => self
+                .device
+                .smbus_write_byte_data(MCP23017_OLATA, self.port as u8),
+            Expander::Mcp23017 => self.device.smbus_write_word_data(MCP23017_OLATA, self.port),
+        };
+        result.map_err(|e| Error::I2c(e.into()))?;
+
+        self.latched = self.port;
+        Ok(())
+    }
+}
+
+impl<D> Bus for Mcp230xxBus<D>
+where
+    D: I2CDevice,
+    D::Error: Into<io::Error>,
+{
+    fn mode(&self) -> Mode {
+        Mode::Bits4
+    }
+
+    /// Latches RS in the port value; it reaches the expander with the next write.
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        self.port = self.layout.set(self.port, self.layout.rs, register == Register::Data);
+        Ok(())
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        self.port = self.layout.set(self.port, self.layout.enable, high);
+        self.write_port()
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.port = self.layout.with_data(self.port, nibble);
+        self.write_port()
+    }
+
+    fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+        Err(Error::Unsupported("8-bit mode"))
+    }
+
+    fn can_read(&self) -> bool {
+        self.layout.rw.is_some()
+    }
+
+    fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+        let rw = match self.layout.rw {
+            Some(rw) => rw,
+            None if read => return Err(Error::Unsupported("read")),
+            None => return Ok(()),
+        };
+
+        let iodir = if read {
+            self.iodir | self.layout.data_mask()
+        } else {
+            self.iodir & !self.layout.data_mask()
+        };
+        if iodir != self.iodir {
+            self.iodir = iodir;
+            self.write_register(MCP23008_IODIR, MCP23017_IODIRA, iodir)?;
+        }
+
+        self.port = self.layout.set(self.port, rw, read);
+        self.write_port()
+    }
+
+    fn read_data(&mut self) -> Result<u8, Error> {
+        if self.layout.rw.is_none() {
+            return Err(Error::Unsupported("read"));
+        }
+
+        let value = self.read_port()?;
+        Ok(self.layout.data_of(value))
+    }
+
+    fn set_backlight(&mut self, on: bool) -> Result<(), Error> {
+        if self.rgb.is_some() {
+            return self.set_color(on, on, on);
+        }
+        if self.layout.backlight.is_none() {
+            return Err(Error::Unsupported("backlight"));
+        }
+
+        self.port = self.layout.with_backlight(self.port, on);
+        self.write_port()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::NoDelay;
+    use crate::sim::SimBus;
+    use crate::{CharSize, LCD};
+
+    /// MCP230xx register file (IOCON.BANK = 0) whose output latches drive a simulated display
+    struct FakeExpander {
+        expander: Expander,
+        layout: BitLayout,
+        registers: [u8; 0x16],
+        pointer: usize,
+        inputs: u16,
+        writes: Vec<Vec<u8>>,
+        display: SimBus,
+    }
+
+    impl FakeExpander {
+        fn new(expander: Expander, layout: BitLayout) -> Self {
+            FakeExpander {
+                expander,
+                layout,
+                registers: [0; 0x16],
+                pointer: 0,
+                inputs: 0xffff,
+                writes: Vec::new(),
+                display: SimBus::new(Mode::Bits4, 16, 2),
+            }
+        }
+
+        fn register16(&self, register: u8) -> u16 {
+            match self.expander {
+                Expander::Mcp23008 => self.registers[register as usize] as u16,
+                Expander::Mcp23017 => {
+                    let register = register as usize;
+                    u16::from_le_bytes([self.registers[register], self.registers[register + 1]])
+                }
+            }
+        }
+
+        fn outputs(&self) -> u16 {
+            match self.expander {
+                Expander::Mcp23008 => self.register16(MCP23008_OLAT),
+                Expander::Mcp23017 => self.register16(MCP23017_OLATA),
+            }
+        }
+
+        fn drive_display(&mut self) {
+            let port = self.outputs();
+            let high = |bit: u8| port & (1 << bit) != 0;
+            let register = if high(self.layout.rs) {
+                Register::Data
+            } else {
+                Register::Instruction
+            };
+            self.display.set_rs(register).unwrap();
+            self.display.set_rw(self.layout.rw.is_some_and(high)).unwrap();
+            self.display.write_nibble(self.layout.data_of(port)).unwrap();
+            self.display.set_enable(high(self.layout.enable)).unwrap();
+        }
+    }
+
+    impl I2CDevice for FakeExpander {
+        type Error = io::Error;
+
+        fn read(&mut self, data: &mut [u8]) -> io::Result<()> {
+            let (gpio, iodir) = match self.expander {
+                Expander::Mcp23008 => (MCP23008_GPIO, MCP23008_IODIR),
+                Expander::Mcp23017 => (MCP23017_GPIOA, MCP23017_IODIRA),
+            };
+            assert_eq!(self.pointer, gpio as usize);
+
+            let mut levels = self.inputs;
+            if self.layout.rw.is_some_and(|rw| self.outputs() & (1 << rw) != 0) {
+                self.display.set_enable(true).unwrap();
+                let nibble = self.display.read_data().unwrap();
+                self.display.set_enable(false).unwrap();
+                levels = self.layout.with_data(levels, nibble);
+            }
+
+            let iodir = self.register16(iodir);
+            let value = (levels & iodir) | (self.outputs() & !iodir);
+            for (i, byte) in data.iter_mut().enumerate() {
+                *byte = value.to_le_bytes()[i];
+            }
+            Ok(())
+        }
+
+        fn write(&mut self, data: &[u8]) -> io::Result<()> {
+            self.writes.push(data.to_vec());
+            self.pointer = data[0] as usize;
+            for (i, value) in data[1..].iter().enumerate() {
+                self.registers[self.pointer + i] = *value;
+            }
+            if data.len() > 1 {
+                self.drive_display();
+            }
+            Ok(())
+        }
+
+        fn smbus_write_quick(&mut self, _bit: bool) -> io::Result<()> {
+            Err(unused())
+        }
+
+        fn smbus_read_block_data(&mut self, _register: u8) -> io::Result<Vec<u8>> {
+            Err(unused())
+        }
+
+        fn smbus_read_i2c_block_data(&mut self, _register: u8, _len: u8) -> io::Result<Vec<u8>> {
+            Err(unused())
+        }
+
+        fn smbus_write_block_data(&mut self, _register: u8, _values: &[u8]) -> io::Result<()> {
+            Err(unused())
+        }
+
+        fn smbus_write_i2c_block_data(&mut self, _register: u8, _values: &[u8]) -> io::Result<()> {
+            Err(unused())
+        }
+
+        fn smbus_process_block(&mut self, _register: u8, _values: &[u8]) -> io::Result<Vec<u8>> {
+            Err(unused())
+        }
+    }
+
+    fn rgb_shield() -> Mcp230xxBus<FakeExpander> {
+        let layout = BitLayout::MCP23017_RGB_SHIELD;
+        let device = FakeExpander::new(Expander::Mcp23017, layout);
+        Mcp230xxBus::with_rgb_backlight(device, Expander::Mcp23017, layout, RGB_SHIELD_BACKLIGHT)
+            .unwrap()
+    }
+
+    fn unused() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "not used by Mcp230xxBus")
+    }
+
+    #[test]
+    fn configures_directions_and_pull_ups() {
+        let bus = rgb_shield();
+
+        assert_eq!(bus.device().register16(MCP23017_IODIRA), 0x003f);
+        assert_eq!(bus.device().register16(MCP23017_GPPUA), 0x003f);
+        assert_eq!(bus.device().outputs(), 0x0000);
+    }
+
+    #[test]
+    fn drives_display_through_expander() {
+        let layout = BitLayout::MCP23008_ADAFRUIT;
+        let device = FakeExpander::new(Expander::Mcp23008, layout);
+        let bus = Mcp230xxBus::new(device, Expander::Mcp23008, layout).unwrap();
+        let mut lcd = LCD::with_bus_and_delay(bus, NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.print("MCP23008").unwrap();
+
+        let device = lcd.release().release();
+        assert_eq!(device.display.screen()[0], "MCP23008        ");
+        assert_eq!(device.outputs() & 0x80, 0x80);
+    }
+
+    #[test]
+    fn writes_one_register_per_nibble() {
+        let mut bus = rgb_shield();
+        bus.device.writes.clear();
+        bus.set_rs(Register::Data).unwrap();
+        bus.write_nibble(0x4).unwrap();
+        bus.set_enable(true).unwrap();
+        bus.set_enable(false).unwrap();
+
+        assert_eq!(
+            bus.device().writes,
+            vec![vec![MCP23017_OLATB, 0x84], vec![MCP23017_OLATB, 0xa4], vec![MCP23017_OLATB, 0x84]]
+        );
+    }
+
+    #[test]
+    fn reads_buttons_and_display() {
+        let mut lcd = LCD::with_bus_and_delay(rgb_shield(), NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.bus_mut().device.inputs = !0b00101;
+
+        let buttons = lcd.bus_mut().buttons().unwrap();
+        assert_eq!(
+            buttons,
+            Buttons {
+                select: true,
+                down: true,
+                ..Buttons::default()
+            }
+        );
+
+        let bus = lcd.bus_mut();
+        bus.set_rs(Register::Instruction).unwrap();
+        bus.set_rw(true).unwrap();
+        assert_eq!(bus.device().register16(MCP23017_IODIRA), 0x1e3f);
+        bus.read_data().unwrap();
+        bus.set_rw(false).unwrap();
+        assert_eq!(bus.device().register16(MCP23017_IODIRA), 0x003f);
+    }
+
+    #[test]
+    fn switches_rgb_backlight() {
+        let mut bus = rgb_shield();
+        bus.set_color(true, false, true).unwrap();
+        assert_eq!(bus.device().outputs(), 0x0080);
+
+        bus.set_backlight(false).unwrap();
+        assert_eq!(bus.device().outputs(), 0x01c0);
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let layout = BitLayout::MCP23017_RGB_SHIELD;
+        let device = FakeExpander::new(Expander::Mcp23008, layout);
+        let narrow = Mcp230xxBus::new(device, Expander::Mcp23008, layout);
+        assert!(matches!(narrow, Err(Error::InvalidPinConfig(_))));
+
+        let layout = BitLayout { rs: 16, ..BitLayout::MCP23017_RGB_SHIELD };
+        let device = FakeExpander::new(Expander::Mcp23017, layout);
+        let overflow = Mcp230xxBus::new(device, Expander::Mcp23017, layout);
+        assert!(matches!(overflow, Err(Error::InvalidPinConfig(_))));
+
+        let layout = BitLayout::MCP23017_RGB_SHIELD;
+        let device = FakeExpander::new(Expander::Mcp23017, layout);
+        let shared = Mcp230xxBus::with_rgb_backlight(device, Expander::Mcp23017, layout, [6, 7, 9]);
+        assert!(matches!(shared, Err(Error::InvalidPinConfig(_))));
+    }
+}
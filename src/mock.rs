@@ -0,0 +1,351 @@
+//! In-memory [Bus](../trait.Bus.html) implementation for testing code that drives the LCD
+//!
+//! [MockBus](struct.MockBus.html) records every transition of the RS, RW, ENABLE and data lines
+//! together with the time it happened, and decodes them back into the instructions and data bytes
+//! the HD44780 controller would have received.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rpi_lcd::mock::{MockBus, Transfer};
+//! use rpi_lcd::{CharSize, Mode, LCD};
+//!
+//! let mut lcd = LCD::with_bus(MockBus::new(Mode::Bits4));
+//! lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+//! lcd.bus_mut().clear();
+//!
+//! lcd.print("Hi").unwrap();
+//!
+//! assert_eq!(lcd.bus().transfers(), vec![Transfer::Data(b'H'), Transfer::Data(b'i')]);
+//! ```
+
+use crate::{Bus, Error, Mode, Register};
+use embedded_hal::blocking::delay::DelayUs;
+use std::time::{Duration, Instant};
+
+/// LCD line observed by [MockBus](struct.MockBus.html)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Pin {
+    /// Register select line
+    Rs,
+
+    /// Read/write line
+    Rw,
+
+    /// Enable line
+    Enable,
+
+    /// Data line d0 to d7
+    Data(u8),
+}
+
+/// Single change of a line level
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transition {
+    /// Time elapsed since the mock was created
+    pub at: Duration,
+
+    /// Line that changed
+    pub pin: Pin,
+
+    /// New level of the line
+    pub high: bool,
+}
+
+/// Value latched by the controller on the falling edge of the ENABLE line
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Transfer {
+    /// Byte written to the instruction register
+    Instruction(u8),
+
+    /// Byte written to the data register (DDRAM or CGRAM)
+    Data(u8),
+}
+
+/// Decodes the HD44780 interface protocol: latches data lines on the falling edge of ENABLE and,
+/// when the controller works in 4-bit mode, joins nibbles into bytes.
+///
+/// The controller always starts in 8-bit mode and switches the interface width whenever it
+/// receives the function set instruction.
+#[derive(Debug)]
+pub(crate) struct Decoder {
+    wiring: Mode,
+    interface: Mode,
+    high_nibble: Option<u8>,
+}
+
+impl Decoder {
+    pub(crate) fn new(wiring: Mode) -> Decoder {
+        Decoder {
+            wiring,
+            interface: Mode::Bits8,
+            high_nibble: None,
+        }
+    }
+
+    /// Latches `data` (d0 to d7) written to `register`. Returns the complete transfer, if any.
+    pub(crate) fn latch(&mut self, register: Register, data: u8) -> Option<Transfer> {
+        let value = match self.interface {
+            // d0 to d3 read as 0 when they are not wired
+            Mode::Bits8 if self.wiring == Mode::Bits4 => data & 0xf0,
+            Mode::Bits8 => data,
+            Mode::Bits4 => match self.high_nibble.take() {
+                None => {
+                    self.high_nibble = Some(data & 0xf0);
+                    return None;
+                }
+                Some(high) => high | (data >> 4),
+            },
+        };
+
+        let transfer = match register {
+            Register::Instruction => Transfer::Instruction(value),
+            Register::Data => Transfer::Data(value),
+        };
+
+        if register == Register::Instruction && value & 0xe0 == 0x20 {
+            self.interface = if value & Mode::Bits8 as u8 != 0 {
+                Mode::Bits8
+            } else {
+                Mode::Bits4
+            };
+        }
+
+        Some(transfer)
+    }
+}
+
+/// [Bus](../trait.Bus.html) implementation that records the line transitions in memory
+pub struct MockBus {
+    mode: Mode,
+    started: Instant,
+    rs: bool,
+    rw: bool,
+    enable: bool,
+    data: u8,
+    transitions: Vec<Transition>,
+    transfers: Vec<Transfer>,
+    decoder: Decoder,
+}
+
+impl MockBus {
+
+    /// Creates a mock of the display wired with `mode` data bus width. All lines start low.
+    pub fn new(mode: Mode) -> MockBus {
+        MockBus {
+            mode,
+            started: Instant::now(),
+            rs: false,
+            rw: false,
+            enable: false,
+            data: 0,
+            transitions: Vec::new(),
+            transfers: Vec::new(),
+            decoder: Decoder::new(mode),
+        }
+    }
+
+    /// Returns all line transitions recorded so far
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    /// Returns the instructions and data bytes decoded from the recorded transitions
+    pub fn transfers(&self) -> Vec<Transfer> {
+        self.transfers.clone()
+    }
+
+    /// Returns the decoded instructions, skipping data bytes
+    pub fn instructions(&self) -> Vec<u8> {
+        self.transfers
+            .iter()
+            .filter_map(|t| match t {
+                Transfer::Instruction(value) => Some(*value),
+                Transfer::Data(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns the decoded data bytes, skipping instructions
+    pub fn data(&self) -> Vec<u8> {
+        self.transfers
+            .iter()
+            .filter_map(|t| match t {
+                Transfer::Data(value) => Some(*value),
+                Transfer::Instruction(_) => None,
+            })
+            .collect()
+    }
+
+    /// Forgets the recorded transitions and transfers. The decoder state (interface width,
+    /// pending nibble) is kept.
+    pub fn clear(&mut self) {
+        self.transitions.clear();
+        self.transfers.clear();
+    }
+
+    fn record(&mut self, pin: Pin, high: bool) {
+        self.transitions.push(Transition {
+            at: self.started.elapsed(),
+            pin,
+            high,
+        });
+    }
+
+    fn set_data(&mut self, value: u8, lines: std::ops::Range<u8>) {
+        for i in lines {
+            let high = value & (1 << i) != 0;
+            if high != (self.data & (1 << i) != 0) {
+                self.data ^= 1 << i;
+                self.record(Pin::Data(i), high);
+            }
+        }
+    }
+}
+
+impl Bus for MockBus {
+    fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        let high = register == Register::Data;
+        if high != self.rs {
+            self.rs = high;
+            self.record(Pin::Rs, high);
+        }
+        Ok(())
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        if high == self.enable {
+            return Ok(());
+        }
+
+        self.enable = high;
+        self.record(Pin::Enable, high);
+
+        if !high && !self.rw {
+            let register = if self.rs {
+                Register::Data
+            } else {
+                Register::Instruction
+            };
+            if let Some(transfer) = self.decoder.latch(register, self.data) {
+                self.transfers.push(transfer);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.set_data(nibble << 4, 4..8);
+        Ok(())
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.set_data(byte, 0..8);
+        Ok(())
+    }
+
+    fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+        if read != self.rw {
+            self.rw = read;
+            self.record(Pin::Rw, read);
+        }
+        Ok(())
+    }
+}
+
+/// Delay that returns immediately, for tests that do not care about the protocol timing
+///
+/// ```rust
+/// use rpi_lcd::mock::{MockBus, NoDelay};
+/// use rpi_lcd::{CharSize, Mode, LCD};
+///
+/// let mut lcd = LCD::with_bus_and_delay(MockBus::new(Mode::Bits4), NoDelay);
+/// lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoDelay;
+
+impl DelayUs<u32> for NoDelay {
+    fn delay_us(&mut self, _us: u32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pulse(bus: &mut MockBus) {
+        bus.set_enable(true).unwrap();
+        bus.set_enable(false).unwrap();
+    }
+
+    #[test]
+    fn records_only_changes() {
+        let mut bus = MockBus::new(Mode::Bits4);
+        bus.set_rs(Register::Instruction).unwrap();
+        bus.write_nibble(0b0101).unwrap();
+        bus.write_nibble(0b0101).unwrap();
+        bus.set_rs(Register::Data).unwrap();
+
+        let pins: Vec<(Pin, bool)> = bus.transitions().iter().map(|t| (t.pin, t.high)).collect();
+        assert_eq!(
+            pins,
+            vec![(Pin::Data(4), true), (Pin::Data(6), true), (Pin::Rs, true)]
+        );
+        assert!(bus.transitions().windows(2).all(|w| w[0].at <= w[1].at));
+    }
+
+    #[test]
+    fn decodes_4_bit_initialization() {
+        let mut bus = MockBus::new(Mode::Bits4);
+        bus.set_rs(Register::Instruction).unwrap();
+        for nibble in &[0x3, 0x3, 0x3, 0x2, 0x2, 0x8] {
+            bus.write_nibble(*nibble).unwrap();
+            pulse(&mut bus);
+        }
+        bus.set_rs(Register::Data).unwrap();
+        bus.write_nibble(0x4).unwrap();
+        pulse(&mut bus);
+        bus.write_nibble(0x1).unwrap();
+        pulse(&mut bus);
+
+        assert_eq!(
+            bus.transfers(),
+            vec![
+                Transfer::Instruction(0x30),
+                Transfer::Instruction(0x30),
+                Transfer::Instruction(0x30),
+                Transfer::Instruction(0x20),
+                Transfer::Instruction(0x28),
+                Transfer::Data(b'A'),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_8_bit_transfers() {
+        let mut bus = MockBus::new(Mode::Bits8);
+        bus.set_rs(Register::Instruction).unwrap();
+        bus.write_byte(0x38).unwrap();
+        pulse(&mut bus);
+        bus.set_rs(Register::Data).unwrap();
+        bus.write_byte(b'z').unwrap();
+        pulse(&mut bus);
+
+        assert_eq!(bus.instructions(), vec![0x38]);
+        assert_eq!(bus.data(), vec![b'z']);
+    }
+
+    #[test]
+    fn ignores_pulses_while_reading() {
+        let mut bus = MockBus::new(Mode::Bits8);
+        bus.set_rw(true).unwrap();
+        pulse(&mut bus);
+        bus.set_rw(false).unwrap();
+
+        assert!(bus.transfers().is_empty());
+    }
+}
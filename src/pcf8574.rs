@@ -0,0 +1,308 @@
+use crate::{BitLayout, Bus, Error, Mode, Register};
+use i2cdev::core::I2CDevice;
+use i2cdev::linux::LinuxI2CDevice;
+use std::io;
+use std::path::Path;
+
+/// [Bus](trait.Bus.html) implementation for LCD connected through a PCF8574 or PCF8574A I2C
+/// "backpack"
+///
+/// The expander drives the display in 4-bit mode. The assignment of the expander bits to the LCD
+/// lines differs between board variants and is described by [BitLayout](struct.BitLayout.html).
+/// The backlight is switched on when the bus is created.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{CharSize, Pcf8574Bus, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let bus = Pcf8574Bus::open("/dev/i2c-1", Pcf8574Bus::ADDRESS)?;
+/// let mut lcd = LCD::with_bus(bus);
+/// lcd.begin(16, 2, CharSize::Dots5x8)?;
+/// lcd.print("Hello over I2C")?;
+/// lcd.no_backlight()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Pcf8574Bus<D> {
+    device: D,
+    layout: BitLayout,
+    port: u16,
+    backlight: bool,
+}
+
+impl Pcf8574Bus<LinuxI2CDevice> {
+
+    /// Default address of PCF8574 (A0 to A2 pulled high)
+    pub const ADDRESS: u16 = 0x27;
+
+    /// Default address of PCF8574A (A0 to A2 pulled high)
+    pub const ADDRESS_A: u16 = 0x3f;
+
+    /// Opens the expander at `address` on the Linux I2C bus device `path` (e.g. `/dev/i2c-1`),
+    /// assuming the [BitLayout::PCF8574](struct.BitLayout.html#associatedconstant.PCF8574)
+    /// layout.
+    pub fn open<P: AsRef<Path>>(path: P, address: u16) -> Result<Self, Error> {
+        let device = LinuxI2CDevice::new(path, address).map_err(|e| Error::I2c(e.into()))?;
+        Pcf8574Bus::new(device, BitLayout::PCF8574)
+    }
+}
+
+impl<D> Pcf8574Bus<D>
+where
+    D: I2CDevice,
+    D::Error: Into<io::Error>,
+{
+
+    /// Creates the bus talking to the expander through `device`, wired according to `layout`,
+    /// and switches the backlight on.
+    ///
+    /// Fails with `Error::InvalidPinConfig` if `layout` uses a bit above 7 or the same bit for
+    /// more than one line.
+    pub fn new(device: D, layout: BitLayout) -> Result<Self, Error> {
+        layout.checked_mask(8, &[])?;
+        let mut bus = Pcf8574Bus {
+            device,
+            layout,
+            port: 0,
+            backlight: true,
+        };
+        bus.write_port()?;
+        Ok(bus)
+    }
+
+    /// Returns the I2C device the bus talks through
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
+    /// Consumes the bus and returns the I2C device it was talking through
+    pub fn release(self) -> D {
+        self.device
+    }
+
+    fn write_port(&mut self) -> Result<(), Error> {
+        let value = self.layout.with_backlight(self.port, self.backlight) as u8;
+        self.device.write(&[value]).map_err(|e| Error::I2c(e.into()))
+    }
+}
+
+impl<D> Bus for Pcf8574Bus<D>
+where
+    D: I2CDevice,
+    D::Error: Into<io::Error>,
+{
+    fn mode(&self) -> Mode {
+        Mode::Bits4
+    }
+
+    /// Latches RS in the port value; it reaches the expander with the next write.
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        self.port = self.layout.set(self.port, self.layout.rs, register == Register::Data);
+        Ok(())
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        self.port = self.layout.set(self.port, self.layout.enable, high);
+        self.write_port()
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.port = self.layout.with_data(self.port, nibble);
+        self.write_port()
+    }
+
+    fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+        Err(Error::Unsupported("8-bit mode"))
+    }
+
+    fn can_read(&self) -> bool {
+        self.layout.rw.is_some()
+    }
+
+    /// PCF8574 pins are quasi-bidirectional: data bits are driven high so that the display can
+    /// pull them down while being read.
+    fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+        let rw = match self.layout.rw {
+            Some(rw) => rw,
+            None if read => return Err(Error::Unsupported("read")),
+            None => return Ok(()),
+        };
+
+        self.port = self.layout.set(self.port, rw, read);
+        if read {
+            self.port = self.layout.with_data(self.port, 0x0f);
+        }
+        self.write_port()
+    }
+
+    fn read_data(&mut self) -> Result<u8, Error> {
+        if self.layout.rw.is_none() {
+            return Err(Error::Unsupported("read"));
+        }
+
+        let mut buf = [0u8];
+        self.device.read(&mut buf).map_err(|e| Error::I2c(e.into()))?;
+        Ok(self.layout.data_of(buf[0] as u16))
+    }
+
+    fn set_backlight(&mut self, on: bool) -> Result<(), Error> {
+        if self.layout.backlight.is_none() {
+            return Err(Error::Unsupported("backlight"));
+        }
+
+        self.backlight = on;
+        self.write_port()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::NoDelay;
+    use crate::sim::SimBus;
+    use crate::{CharSize, LCD};
+
+    /// PCF8574 whose port is wired to a simulated display
+    struct FakeExpander {
+        layout: BitLayout,
+        writes: Vec<u8>,
+        display: SimBus,
+    }
+
+    impl FakeExpander {
+        fn new(layout: BitLayout) -> Self {
+            FakeExpander {
+                layout,
+                writes: Vec::new(),
+                display: SimBus::new(Mode::Bits4, 16, 2),
+            }
+        }
+    }
+
+    impl I2CDevice for FakeExpander {
+        type Error = io::Error;
+
+        fn read(&mut self, data: &mut [u8]) -> io::Result<()> {
+            let port = self.layout.with_data(*self.writes.last().unwrap() as u16, 0);
+            self.display.set_enable(true).unwrap();
+            let nibble = self.display.read_data().unwrap();
+            self.display.set_enable(false).unwrap();
+            data[0] = self.layout.with_data(port, nibble) as u8;
+            Ok(())
+        }
+
+        fn write(&mut self, data: &[u8]) -> io::Result<()> {
+            let port = data[0] as u16;
+            let high = |bit: u8| port & (1 << bit) != 0;
+            self.writes.push(data[0]);
+
+            let register = if high(self.layout.rs) {
+                Register::Data
+            } else {
+                Register::Instruction
+            };
+            self.display.set_rs(register).unwrap();
+            self.display.set_rw(self.layout.rw.is_some_and(high)).unwrap();
+            self.display.write_nibble(self.layout.data_of(port)).unwrap();
+            self.display.set_enable(high(self.layout.enable)).unwrap();
+            Ok(())
+        }
+
+        fn smbus_write_quick(&mut self, _bit: bool) -> io::Result<()> {
+            Err(unused())
+        }
+
+        fn smbus_read_block_data(&mut self, _register: u8) -> io::Result<Vec<u8>> {
+            Err(unused())
+        }
+
+        fn smbus_read_i2c_block_data(&mut self, _register: u8, _len: u8) -> io::Result<Vec<u8>> {
+            Err(unused())
+        }
+
+        fn smbus_write_block_data(&mut self, _register: u8, _values: &[u8]) -> io::Result<()> {
+            Err(unused())
+        }
+
+        fn smbus_write_i2c_block_data(&mut self, _register: u8, _values: &[u8]) -> io::Result<()> {
+            Err(unused())
+        }
+
+        fn smbus_process_block(&mut self, _register: u8, _values: &[u8]) -> io::Result<Vec<u8>> {
+            Err(unused())
+        }
+    }
+
+    fn unused() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "not used by Pcf8574Bus")
+    }
+
+    #[test]
+    fn drives_display_through_expander() {
+        for layout in &[BitLayout::PCF8574, BitLayout::PCF8574_MJKDZ] {
+            let bus = Pcf8574Bus::new(FakeExpander::new(*layout), *layout).unwrap();
+            let mut lcd = LCD::with_bus_and_delay(bus, NoDelay);
+            lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+            lcd.print("I2C").unwrap();
+
+            let device = lcd.release().release();
+            assert_eq!(device.display.screen()[0], "I2C             ");
+        }
+    }
+
+    #[test]
+    fn writes_one_byte_per_line_change() {
+        let layout = BitLayout::PCF8574;
+        let mut bus = Pcf8574Bus::new(FakeExpander::new(layout), layout).unwrap();
+        bus.set_rs(Register::Data).unwrap();
+        bus.write_nibble(0x4).unwrap();
+        bus.set_enable(true).unwrap();
+        bus.set_enable(false).unwrap();
+
+        assert_eq!(bus.device().writes, vec![0x08, 0x49, 0x4d, 0x49]);
+    }
+
+    #[test]
+    fn switches_backlight() {
+        let layout = BitLayout::PCF8574_MJKDZ;
+        let bus = Pcf8574Bus::new(FakeExpander::new(layout), layout).unwrap();
+        assert_eq!(bus.device().writes, vec![0x00]);
+
+        let mut lcd = LCD::with_bus_and_delay(bus, NoDelay);
+        lcd.no_backlight().unwrap();
+        lcd.backlight().unwrap();
+
+        assert_eq!(lcd.bus().device().writes, vec![0x00, 0x80, 0x00]);
+    }
+
+    #[test]
+    fn reads_through_expander() {
+        let layout = BitLayout::PCF8574;
+        let mut bus = Pcf8574Bus::new(FakeExpander::new(layout), layout).unwrap();
+        bus.set_rw(true).unwrap();
+
+        assert!(bus.can_read());
+        assert_eq!(bus.read_data().unwrap(), 0);
+
+        let layout = BitLayout {
+            rw: None,
+            ..BitLayout::PCF8574
+        };
+        let mut bus = Pcf8574Bus::new(FakeExpander::new(layout), layout).unwrap();
+        assert!(!bus.can_read());
+        assert!(matches!(bus.set_rw(true), Err(Error::Unsupported("read"))));
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        for layout in &[
+            BitLayout { rs: 8, ..BitLayout::PCF8574 },
+            BitLayout { data: [4, 5, 6, 16], ..BitLayout::PCF8574 },
+            BitLayout { enable: 0, ..BitLayout::PCF8574 },
+        ] {
+            let bus = Pcf8574Bus::new(FakeExpander::new(*layout), *layout);
+            assert!(matches!(bus, Err(Error::InvalidPinConfig(_))));
+        }
+    }
+}
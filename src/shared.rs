@@ -0,0 +1,209 @@
+use crate::{Bus, Error, GpioBus, GpioLine, Mode, Register, StdDelay, LCD};
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+
+/// Bus shared by several displays wired to the same RS, RW and data lines, each with its own
+/// ENABLE line
+///
+/// The shared bus owns the common lines and hands out a [SharedBusHandle](
+/// struct.SharedBusHandle.html) per display. Each handle drives the ENABLE line of its display
+/// only, so that the displays are controlled by independent `LCD`s. Dropping a handle, or the
+/// `LCD` owning it, makes its display available again.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{CharSize, GpioBus, GpioPin::*, Pins, SharedBus};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let mut shared = SharedBus::new(GpioBus::new(Pins {
+///     rs: P26,
+///     rw: None,
+///     enable: P19,
+///     data: [NONE, NONE, NONE, NONE, P13, P6, P5, P11],
+/// })?);
+///
+/// let mut top = shared.lcd(19.into())?;
+/// let mut bottom = shared.lcd(20.into())?;
+/// top.begin(16, 2, CharSize::Dots5x8)?;
+/// bottom.begin(16, 2, CharSize::Dots5x8)?;
+/// top.print("top")?;
+/// bottom.print("bottom")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SharedBus<B: Bus> {
+    bus: Rc<RefCell<B>>,
+    handed_out: Rc<RefCell<Vec<u8>>>,
+}
+
+impl<B: Bus> SharedBus<B> {
+
+    /// Shares `bus` between the displays connected to its ENABLE lines
+    pub fn new(bus: B) -> SharedBus<B> {
+        SharedBus {
+            bus: Rc::new(RefCell::new(bus)),
+            handed_out: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Returns the bus of the display connected to ENABLE line `index` of the shared bus
+    ///
+    /// Fails with `Error::InvalidPinConfig` if the bus has no such ENABLE line or if the handle
+    /// of the display has already been handed out and not dropped yet.
+    pub fn handle(&mut self, index: u8) -> Result<SharedBusHandle<B>, Error> {
+        if index >= self.bus.borrow().controllers() {
+            return Err(Error::InvalidPinConfig("no such enable line"));
+        }
+        if self.handed_out.borrow().contains(&index) {
+            return Err(Error::InvalidPinConfig("enable line already in use"));
+        }
+
+        self.handed_out.borrow_mut().push(index);
+        Ok(SharedBusHandle {
+            bus: Rc::clone(&self.bus),
+            handed_out: Rc::clone(&self.handed_out),
+            index,
+        })
+    }
+
+    /// Returns a reference to the shared bus
+    pub fn bus(&self) -> Ref<'_, B> {
+        self.bus.borrow()
+    }
+}
+
+impl SharedBus<GpioBus> {
+
+    /// Creates the LCD driving the display whose ENABLE pin is connected to `enable`. The line
+    /// is requested from the chip of the shared lines unless it is already requested.
+    ///
+    /// Fails with `Error::InvalidPinConfig` if an LCD has already been created for `enable`.
+    pub fn lcd(
+        &mut self,
+        enable: GpioLine,
+    ) -> Result<LCD<SharedBusHandle<GpioBus>, StdDelay>, Error> {
+        let index = self.bus.borrow_mut().enable_index(enable)?;
+        Ok(LCD::with_bus(self.handle(index)?))
+    }
+}
+
+/// [Bus](trait.Bus.html) of a single display connected to a [SharedBus](struct.SharedBus.html)
+///
+/// The common lines are driven for every display; the handle selects the ENABLE line of its
+/// display before driving it.
+pub struct SharedBusHandle<B: Bus> {
+    bus: Rc<RefCell<B>>,
+    handed_out: Rc<RefCell<Vec<u8>>>,
+    index: u8,
+}
+
+impl<B: Bus> SharedBusHandle<B> {
+
+    /// Returns the index of the ENABLE line of the display on the shared bus
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+}
+
+impl<B: Bus> Drop for SharedBusHandle<B> {
+    fn drop(&mut self) {
+        self.handed_out.borrow_mut().retain(|index| *index != self.index);
+    }
+}
+
+impl<B: Bus> Bus for SharedBusHandle<B> {
+    fn mode(&self) -> Mode {
+        self.bus.borrow().mode()
+    }
+
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        self.bus.borrow_mut().set_rs(register)
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        let mut bus = self.bus.borrow_mut();
+        bus.select_controller(self.index)?;
+        bus.set_enable(high)
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.bus.borrow_mut().write_nibble(nibble)
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.bus.borrow_mut().write_byte(byte)
+    }
+
+    fn can_read(&self) -> bool {
+        self.bus.borrow().can_read()
+    }
+
+    fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+        self.bus.borrow_mut().set_rw(read)
+    }
+
+    fn read_data(&mut self) -> Result<u8, Error> {
+        self.bus.borrow_mut().read_data()
+    }
+
+    fn set_backlight(&mut self, on: bool) -> Result<(), Error> {
+        self.bus.borrow_mut().set_backlight(on)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::NoDelay;
+    use crate::sim::SimBus;
+    use crate::CharSize;
+
+    #[test]
+    fn displays_have_independent_state() {
+        // two 16x2 displays: rows 0 and 1 of the model show the first one, rows 2 and 3 the second
+        let mut shared = SharedBus::new(SimBus::dual(Mode::Bits4, 16));
+        let mut first = LCD::with_bus_and_delay(shared.handle(0).unwrap(), NoDelay);
+        let mut second = LCD::with_bus_and_delay(shared.handle(1).unwrap(), NoDelay);
+        first.begin(16, 2, CharSize::Dots5x8).unwrap();
+        second.begin(16, 2, CharSize::Dots5x8).unwrap();
+
+        first.print("first").unwrap();
+        second.set_cursor(2, 1).unwrap();
+        second.print("second").unwrap();
+        first.cursor().unwrap();
+        first.print("!").unwrap();
+
+        assert_eq!(
+            shared.bus().screen(),
+            vec!["first!          ", "                ", "                ", "  second        "]
+        );
+        assert!(shared.bus().controller_at(0).is_cursor_on());
+        assert!(!shared.bus().controller_at(1).is_cursor_on());
+        assert_eq!(first.read_char_at(0, 0).unwrap(), b'f');
+        assert_eq!(second.address_counter().unwrap(), 0x48);
+    }
+
+    #[test]
+    fn hands_out_each_display_once() {
+        let mut shared = SharedBus::new(SimBus::dual(Mode::Bits4, 16));
+        let handle = shared.handle(1).unwrap();
+        assert!(matches!(shared.handle(1), Err(Error::InvalidPinConfig(_))));
+        assert!(matches!(shared.handle(2), Err(Error::InvalidPinConfig(_))));
+        assert_eq!(shared.handle(0).unwrap().index(), 0);
+        assert_eq!(handle.index(), 1);
+    }
+
+    #[test]
+    fn dropped_displays_are_handed_out_again() {
+        let mut shared = SharedBus::new(SimBus::dual(Mode::Bits4, 16));
+        let mut lcd = LCD::with_bus_and_delay(shared.handle(1).unwrap(), NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.print("before").unwrap();
+        drop(lcd);
+
+        let mut lcd = LCD::with_bus_and_delay(shared.handle(1).unwrap(), NoDelay);
+        lcd.set_cursor(0, 1).unwrap();
+        lcd.print("after").unwrap();
+        assert_eq!(shared.bus().screen()[2..], ["before          ", "after           "]);
+    }
+}
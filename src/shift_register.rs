@@ -0,0 +1,313 @@
+use crate::{BitLayout, Bus, Error, GpioChip, GpioLine, GpioPin, Mode, Register};
+use gpio_cdev::LineHandle;
+use spidev::{SpiModeFlags, Spidev, SpidevOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Serial output of a byte into a 74HC595 (or compatible) shift register
+///
+/// Implementations shift the 8 bits of the value, most significant first, and then pulse the
+/// storage register clock so that all the outputs change at once.
+pub trait ShiftOut {
+    /// Shifts `value` out most significant bit first and latches it onto the outputs. Returns
+    /// the error of the underlying SPI device or GPIO lines.
+    fn shift_out(&mut self, value: u8) -> Result<(), Error>;
+}
+
+/// The shift register is clocked by SPI: MOSI drives SER, SCLK drives SRCLK and chip select
+/// drives RCLK, latching the outputs at the end of the transfer.
+impl ShiftOut for Spidev {
+    fn shift_out(&mut self, value: u8) -> Result<(), Error> {
+        self.write_all(&[value]).map_err(Error::Spi)
+    }
+}
+
+/// Shift register clocked by bit-banging three GPIO lines
+pub struct GpioShifter {
+    data: LineHandle,
+    clock: LineHandle,
+    latch: LineHandle,
+}
+
+impl GpioShifter {
+
+    /// Requests from `/dev/gpiochip0` the GPIO lines connected to the shift register SER
+    /// (`data`), SRCLK (`clock`) and RCLK (`latch`) pins.
+    pub fn new(data: GpioPin, clock: GpioPin, latch: GpioPin) -> Result<GpioShifter, Error> {
+        if [data, clock, latch].contains(&GpioPin::NONE) {
+            return Err(Error::InvalidPinConfig("data, clock and latch pins are required"));
+        }
+
+        let line = |pin: GpioPin| GpioLine::Offset(pin as u32);
+        GpioShifter::with_lines(GpioChip::default(), line(data), line(clock), line(latch))
+    }
+
+    /// Requests from `chip` the lines connected to the shift register SER (`data`), SRCLK
+    /// (`clock`) and RCLK (`latch`) pins, given by offset or by name.
+    ///
+    /// Fails with `Error::NotFound` if the chip or a named line does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use rpi_lcd::{GpioChip, GpioShifter};
+    /// # fn main() -> Result<(), rpi_lcd::Error> {
+    /// let shifter = GpioShifter::with_lines(
+    ///     GpioChip::Label("pinctrl-rp1".to_string()),
+    ///     "GPIO17".into(),
+    ///     "GPIO27".into(),
+    ///     "GPIO22".into(),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_lines(
+        chip: GpioChip,
+        data: GpioLine,
+        clock: GpioLine,
+        latch: GpioLine,
+    ) -> Result<GpioShifter, Error> {
+        let mut chip = chip.open()?;
+        Ok(GpioShifter {
+            data: data.line_handle(&mut chip, "ser")?,
+            clock: clock.line_handle(&mut chip, "srclk")?,
+            latch: latch.line_handle(&mut chip, "rclk")?,
+        })
+    }
+}
+
+impl ShiftOut for GpioShifter {
+    fn shift_out(&mut self, value: u8) -> Result<(), Error> {
+        self.latch.set_value(0)?;
+        for i in (0..8).rev() {
+            self.clock.set_value(0)?;
+            self.data.set_value((value >> i) & 0x01)?;
+            self.clock.set_value(1)?;
+        }
+        self.latch.set_value(1)?;
+        Ok(())
+    }
+}
+
+/// [Bus](trait.Bus.html) implementation for LCD connected through a 74HC595 shift register
+///
+/// Every change of the LCD lines is shifted out as a whole byte, which lets the display run in
+/// 4-bit mode with only 3 wires, either over SPI or bit-banged GPIO lines (see
+/// [ShiftOut](trait.ShiftOut.html)). The assignment of the register outputs to the LCD lines is
+/// described by [BitLayout](struct.BitLayout.html). The display cannot be read.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rpi_lcd::{CharSize, ShiftRegisterBus, LCD};
+/// # fn main() -> Result<(), rpi_lcd::Error> {
+/// let mut lcd = LCD::with_bus(ShiftRegisterBus::open_spi("/dev/spidev0.0")?);
+/// lcd.begin(16, 2, CharSize::Dots5x8)?;
+/// lcd.print("3 wires")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShiftRegisterBus<S> {
+    shifter: S,
+    layout: BitLayout,
+    port: u16,
+    backlight: bool,
+}
+
+impl ShiftRegisterBus<Spidev> {
+
+    /// SPI clock frequency used by [open_spi()](#method.open_spi)
+    pub const SPI_SPEED_HZ: u32 = 1_000_000;
+
+    /// Opens the SPI device `path` (e.g. `/dev/spidev0.0`) the shift register is connected to,
+    /// assuming the [BitLayout::SHIFT_REGISTER_ADAFRUIT](
+    /// struct.BitLayout.html#associatedconstant.SHIFT_REGISTER_ADAFRUIT) layout.
+    pub fn open_spi<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut spi = Spidev::open(path).map_err(Error::Spi)?;
+        let options = SpidevOptions::new()
+            .bits_per_word(8)
+            .max_speed_hz(Self::SPI_SPEED_HZ)
+            .mode(SpiModeFlags::SPI_MODE_0)
+            .build();
+        spi.configure(&options).map_err(Error::Spi)?;
+        ShiftRegisterBus::new(spi, BitLayout::SHIFT_REGISTER_ADAFRUIT)
+    }
+}
+
+impl ShiftRegisterBus<GpioShifter> {
+
+    /// Creates the bus shifting bits out through the GPIO `data`, `clock` and `latch` lines,
+    /// wired according to `layout`.
+    pub fn open_gpio(
+        data: GpioPin,
+        clock: GpioPin,
+        latch: GpioPin,
+        layout: BitLayout,
+    ) -> Result<Self, Error> {
+        ShiftRegisterBus::new(GpioShifter::new(data, clock, latch)?, layout)
+    }
+}
+
+impl<S: ShiftOut> ShiftRegisterBus<S> {
+
+    /// Creates the bus shifting bits out through `shifter`, wired according to `layout`.
+    ///
+    /// Fails with `Error::InvalidPinConfig` if `layout` uses bits beyond the 8 outputs of the
+    /// shift register, the same bit for more than one line or connects RW, as the display cannot
+    /// be read through the register.
+    pub fn new(shifter: S, layout: BitLayout) -> Result<Self, Error> {
+        layout.checked_mask(8, &[])?;
+        if layout.rw.is_some() {
+            return Err(Error::InvalidPinConfig("rw must be tied to ground"));
+        }
+
+        Ok(ShiftRegisterBus {
+            shifter,
+            layout,
+            port: 0,
+            backlight: true,
+        })
+    }
+
+    /// Returns the shifter the bus writes through
+    pub fn shifter(&self) -> &S {
+        &self.shifter
+    }
+
+    /// Consumes the bus and returns the shifter it was writing through
+    pub fn release(self) -> S {
+        self.shifter
+    }
+
+    fn write_port(&mut self) -> Result<(), Error> {
+        let value = self.layout.with_backlight(self.port, self.backlight) as u8;
+        self.shifter.shift_out(value)
+    }
+}
+
+impl<S: ShiftOut> Bus for ShiftRegisterBus<S> {
+    fn mode(&self) -> Mode {
+        Mode::Bits4
+    }
+
+    /// Latches RS in the port value; it reaches the register with the next write.
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        self.port = self.layout.set(self.port, self.layout.rs, register == Register::Data);
+        Ok(())
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        self.port = self.layout.set(self.port, self.layout.enable, high);
+        self.write_port()
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.port = self.layout.with_data(self.port, nibble);
+        self.write_port()
+    }
+
+    fn write_byte(&mut self, _byte: u8) -> Result<(), Error> {
+        Err(Error::Unsupported("8-bit mode"))
+    }
+
+    fn set_backlight(&mut self, on: bool) -> Result<(), Error> {
+        if self.layout.backlight.is_none() {
+            return Err(Error::Unsupported("backlight"));
+        }
+
+        self.backlight = on;
+        self.write_port()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::NoDelay;
+    use crate::sim::SimBus;
+    use crate::{CharSize, LCD};
+
+    /// Shift register whose outputs are wired to a simulated display
+    struct FakeShifter {
+        layout: BitLayout,
+        values: Vec<u8>,
+        display: SimBus,
+    }
+
+    impl FakeShifter {
+        fn new(layout: BitLayout) -> Self {
+            FakeShifter {
+                layout,
+                values: Vec::new(),
+                display: SimBus::new(Mode::Bits4, 16, 2),
+            }
+        }
+    }
+
+    impl ShiftOut for FakeShifter {
+        fn shift_out(&mut self, value: u8) -> Result<(), Error> {
+            let port = value as u16;
+            let register = if port & (1 << self.layout.rs) != 0 {
+                Register::Data
+            } else {
+                Register::Instruction
+            };
+            self.values.push(value);
+
+            self.display.set_rs(register)?;
+            self.display.write_nibble(self.layout.data_of(port))?;
+            self.display.set_enable(port & (1 << self.layout.enable) != 0)
+        }
+    }
+
+    #[test]
+    fn drives_display_through_shift_register() {
+        let layout = BitLayout::SHIFT_REGISTER_ADAFRUIT;
+        let bus = ShiftRegisterBus::new(FakeShifter::new(layout), layout).unwrap();
+        let mut lcd = LCD::with_bus_and_delay(bus, NoDelay);
+        lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+        lcd.print("74HC595").unwrap();
+
+        let shifter = lcd.release().release();
+        assert_eq!(shifter.display.screen()[0], "74HC595         ");
+    }
+
+    #[test]
+    fn shifts_one_byte_per_line_change() {
+        let layout = BitLayout::SHIFT_REGISTER_ADAFRUIT;
+        let mut bus = ShiftRegisterBus::new(FakeShifter::new(layout), layout).unwrap();
+        bus.set_rs(Register::Data).unwrap();
+        bus.write_nibble(0x1).unwrap();
+        bus.set_enable(true).unwrap();
+        bus.set_enable(false).unwrap();
+        bus.set_backlight(false).unwrap();
+
+        assert_eq!(bus.shifter().values, vec![0xc2, 0xc6, 0xc2, 0x42]);
+    }
+
+    #[test]
+    fn rejects_layout_not_fitting_register() {
+        let layout = BitLayout::MCP23017_RGB_SHIELD;
+        assert!(matches!(
+            ShiftRegisterBus::new(FakeShifter::new(layout), layout),
+            Err(Error::InvalidPinConfig(_))
+        ));
+
+        let layout = BitLayout::PCF8574;
+        assert!(matches!(
+            ShiftRegisterBus::new(FakeShifter::new(layout), layout),
+            Err(Error::InvalidPinConfig(_))
+        ));
+
+        for layout in &[
+            BitLayout { rs: 16, ..BitLayout::SHIFT_REGISTER_ADAFRUIT },
+            BitLayout { backlight: Some(200), ..BitLayout::SHIFT_REGISTER_ADAFRUIT },
+            BitLayout { enable: 1, ..BitLayout::SHIFT_REGISTER_ADAFRUIT },
+        ] {
+            assert!(matches!(
+                ShiftRegisterBus::new(FakeShifter::new(*layout), *layout),
+                Err(Error::InvalidPinConfig(_))
+            ));
+        }
+    }
+}
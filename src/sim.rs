@@ -0,0 +1,825 @@
+//! Software model of the HD44780 controller
+//!
+//! [Hd44780](struct.Hd44780.html) keeps the controller state (address counter, DDRAM, CGRAM,
+//! entry mode, display shift, function set) and executes the instructions it receives.
+//! [SimBus](struct.SimBus.html) plugs the model in as a [Bus](../trait.Bus.html) so that the text
+//! [LCD](../struct.LCD.html) puts on the screen can be verified without the hardware.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rpi_lcd::sim::SimBus;
+//! use rpi_lcd::{CharSize, Mode, LCD};
+//!
+//! let mut lcd = LCD::with_bus(SimBus::new(Mode::Bits4, 16, 2));
+//! lcd.begin(16, 2, CharSize::Dots5x8).unwrap();
+//! lcd.print("Hello,").unwrap();
+//! lcd.set_cursor(0, 1).unwrap();
+//! lcd.print("World!").unwrap();
+//!
+//! assert_eq!(lcd.bus().screen(), vec!["Hello,          ", "World!          "]);
+//! ```
+
+use crate::mock::{Decoder, Transfer};
+use crate::{Bus, Error, Mode, Register};
+
+const LINE_LENGTH: usize = 40;
+const DDRAM_SIZE: usize = 2 * LINE_LENGTH;
+const CGRAM_SIZE: usize = 64;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Target {
+    Ddram,
+    Cgram,
+}
+
+/// Behavioural model of the HD44780 controller
+///
+/// The model starts in the state the controller is in after the internal reset: 8-bit interface,
+/// 1-line display, display off, increment mode without shift. DDRAM is filled with spaces.
+#[derive(Debug, Clone)]
+pub struct Hd44780 {
+    ddram: [u8; DDRAM_SIZE],
+    cgram: [u8; CGRAM_SIZE],
+    address_counter: u8,
+    target: Target,
+    increment: bool,
+    entry_shift: bool,
+    display_on: bool,
+    cursor_on: bool,
+    blink_on: bool,
+    shift: usize,
+    bits8: bool,
+    two_lines: bool,
+    font_5x10: bool,
+}
+
+impl Default for Hd44780 {
+    fn default() -> Self {
+        Hd44780::new()
+    }
+}
+
+impl Hd44780 {
+
+    /// Creates the controller in its power-on reset state
+    pub fn new() -> Hd44780 {
+        Hd44780 {
+            ddram: [b' '; DDRAM_SIZE],
+            cgram: [0; CGRAM_SIZE],
+            address_counter: 0,
+            target: Target::Ddram,
+            increment: true,
+            entry_shift: false,
+            display_on: false,
+            cursor_on: false,
+            blink_on: false,
+            shift: 0,
+            bits8: true,
+            two_lines: false,
+            font_5x10: false,
+        }
+    }
+
+    /// Executes an instruction written to the instruction register
+    pub fn instruction(&mut self, value: u8) {
+        if value & 0x80 != 0 {
+            self.target = Target::Ddram;
+            self.address_counter = value & 0x7f;
+        } else if value & 0x40 != 0 {
+            self.target = Target::Cgram;
+            self.address_counter = value & 0x3f;
+        } else if value & 0x20 != 0 {
+            self.bits8 = value & 0x10 != 0;
+            self.two_lines = value & 0x08 != 0;
+            self.font_5x10 = value & 0x04 != 0;
+        } else if value & 0x10 != 0 {
+            let right = value & 0x04 != 0;
+            if value & 0x08 != 0 {
+                self.shift_display(!right);
+            } else {
+                self.step_address(right);
+            }
+        } else if value & 0x08 != 0 {
+            self.display_on = value & 0x04 != 0;
+            self.cursor_on = value & 0x02 != 0;
+            self.blink_on = value & 0x01 != 0;
+        } else if value & 0x04 != 0 {
+            self.increment = value & 0x02 != 0;
+            self.entry_shift = value & 0x01 != 0;
+        } else if value & 0x02 != 0 {
+            self.target = Target::Ddram;
+            self.address_counter = 0;
+            self.shift = 0;
+        } else if value & 0x01 != 0 {
+            self.ddram = [b' '; DDRAM_SIZE];
+            self.target = Target::Ddram;
+            self.address_counter = 0;
+            self.shift = 0;
+            self.increment = true;
+        }
+    }
+
+    /// Writes a byte to DDRAM or CGRAM, depending on the last set address instruction
+    pub fn data(&mut self, value: u8) {
+        match self.target {
+            Target::Ddram => {
+                let index = self.ddram_index(self.address_counter);
+                self.ddram[index] = value;
+                if self.entry_shift {
+                    self.shift_display(self.increment);
+                }
+            }
+            Target::Cgram => self.cgram[self.address_counter as usize] = value,
+        }
+        self.step_address(self.increment);
+    }
+
+    /// Returns the busy flag (always clear) and the address counter, as read from the
+    /// instruction register
+    pub fn status(&self) -> u8 {
+        self.address_counter
+    }
+
+    /// Reads a byte from DDRAM or CGRAM at the address counter and moves the counter
+    pub fn read(&mut self) -> u8 {
+        let value = match self.target {
+            Target::Ddram => self.ddram[self.ddram_index(self.address_counter)],
+            Target::Cgram => self.cgram[self.address_counter as usize],
+        };
+        self.step_address(self.increment);
+        value
+    }
+
+    /// Returns the address counter
+    pub fn address_counter(&self) -> u8 {
+        self.address_counter
+    }
+
+    /// Returns the byte stored in DDRAM at `address`
+    pub fn ddram(&self, address: u8) -> u8 {
+        self.ddram[self.ddram_index(address)]
+    }
+
+    /// Returns the CGRAM contents
+    pub fn cgram(&self) -> &[u8] {
+        &self.cgram
+    }
+
+    /// Returns the number of positions the display has been shifted to the left
+    pub fn display_shift(&self) -> usize {
+        self.shift
+    }
+
+    /// Returns `true` if the display is on
+    pub fn is_display_on(&self) -> bool {
+        self.display_on
+    }
+
+    /// Returns `true` if the cursor is shown
+    pub fn is_cursor_on(&self) -> bool {
+        self.cursor_on
+    }
+
+    /// Returns `true` if the cursor blinks
+    pub fn is_blink_on(&self) -> bool {
+        self.blink_on
+    }
+
+    /// Returns `true` if the address counter is incremented after each DDRAM or CGRAM access
+    pub fn is_increment(&self) -> bool {
+        self.increment
+    }
+
+    /// Returns `true` if the display shifts after each DDRAM write
+    pub fn is_entry_shift(&self) -> bool {
+        self.entry_shift
+    }
+
+    /// Returns the data length set by the last function set instruction
+    pub fn mode(&self) -> Mode {
+        if self.bits8 {
+            Mode::Bits8
+        } else {
+            Mode::Bits4
+        }
+    }
+
+    /// Returns `true` if the controller is set to 2-line display
+    pub fn is_two_lines(&self) -> bool {
+        self.two_lines
+    }
+
+    /// Returns `true` if the controller is set to 5x10 dots font
+    pub fn is_font_5x10(&self) -> bool {
+        self.font_5x10
+    }
+
+    /// Returns the byte shown at `col` and `row` of a `cols` wide panel
+    ///
+    /// Panels with more than 2 rows continue controller line 1 on row 3 and line 2 on row 4.
+    /// Returns a space if the display is off or the row is not driven by the controller.
+    pub fn visible(&self, cols: u8, col: u8, row: u8) -> u8 {
+        let line = if self.two_lines { row % 2 } else { row };
+        if !self.display_on || line > 1 || (!self.two_lines && line > 0) {
+            return b' ';
+        }
+
+        let position = (row / 2) as usize * cols as usize + col as usize;
+        if self.two_lines {
+            self.ddram[line as usize * LINE_LENGTH + (position + self.shift) % LINE_LENGTH]
+        } else {
+            self.ddram[(position + self.shift) % DDRAM_SIZE]
+        }
+    }
+
+    fn line_length(&self) -> usize {
+        if self.two_lines {
+            LINE_LENGTH
+        } else {
+            DDRAM_SIZE
+        }
+    }
+
+    fn ddram_index(&self, address: u8) -> usize {
+        if self.two_lines {
+            let line = if address & 0x40 != 0 { 1 } else { 0 };
+            line * LINE_LENGTH + (address & 0x3f) as usize % LINE_LENGTH
+        } else {
+            address as usize % DDRAM_SIZE
+        }
+    }
+
+    fn shift_display(&mut self, left: bool) {
+        let length = self.line_length();
+        self.shift = if left {
+            (self.shift + 1) % length
+        } else {
+            (self.shift + length - 1) % length
+        };
+    }
+
+    fn step_address(&mut self, increment: bool) {
+        let address = self.address_counter;
+        self.address_counter = match (self.target, self.two_lines, increment) {
+            (Target::Cgram, _, true) => (address + 1) & 0x3f,
+            (Target::Cgram, _, false) => address.wrapping_sub(1) & 0x3f,
+            (Target::Ddram, true, true) => match address {
+                0x27 => 0x40,
+                0x67 => 0x00,
+                _ => address + 1,
+            },
+            (Target::Ddram, true, false) => match address {
+                0x00 => 0x67,
+                0x40 => 0x27,
+                _ => address - 1,
+            },
+            (Target::Ddram, false, true) => {
+                if address >= 0x4f {
+                    0x00
+                } else {
+                    address + 1
+                }
+            }
+            (Target::Ddram, false, false) => {
+                if address == 0x00 {
+                    0x4f
+                } else {
+                    address - 1
+                }
+            }
+        };
+    }
+}
+
+/// [Bus](../trait.Bus.html) implementation that feeds an [Hd44780](struct.Hd44780.html) model
+/// attached to a panel of the given size, or two models for the 40x4 displays made of two
+/// controllers
+pub struct SimBus {
+    mode: Mode,
+    cols: u8,
+    rows: u8,
+    rs: bool,
+    rw: bool,
+    enable: bool,
+    data: u8,
+    output: u8,
+    read_low_nibble: bool,
+    decoders: Vec<Decoder>,
+    controllers: Vec<Hd44780>,
+    selected: usize,
+}
+
+impl SimBus {
+
+    /// Creates the model of a `cols` x `rows` display wired with `mode` data bus width
+    pub fn new(mode: Mode, cols: u8, rows: u8) -> SimBus {
+        SimBus::with_controllers(mode, cols, rows, 1)
+    }
+
+    /// Creates the model of a `cols` x 4 display made of two controllers, the first one driving
+    /// rows 0 and 1 and the second one rows 2 and 3
+    pub fn dual(mode: Mode, cols: u8) -> SimBus {
+        SimBus::with_controllers(mode, cols, 4, 2)
+    }
+
+    fn with_controllers(mode: Mode, cols: u8, rows: u8, count: usize) -> SimBus {
+        SimBus {
+            mode,
+            cols,
+            rows,
+            rs: false,
+            rw: false,
+            enable: false,
+            data: 0,
+            output: 0,
+            read_low_nibble: false,
+            decoders: (0..count).map(|_| Decoder::new(mode)).collect(),
+            controllers: vec![Hd44780::new(); count],
+            selected: 0,
+        }
+    }
+
+    /// Returns the controller model
+    pub fn controller(&self) -> &Hd44780 {
+        &self.controllers[0]
+    }
+
+    /// Returns the model of controller `index`, 0 being the first. Panics if the display has no
+    /// such controller.
+    pub fn controller_at(&self, index: u8) -> &Hd44780 {
+        &self.controllers[index as usize]
+    }
+
+    /// Returns the text visible on the panel, one string per row. Bytes are mapped to characters
+    /// one to one.
+    pub fn screen(&self) -> Vec<String> {
+        (0..self.rows)
+            .map(|row| {
+                (0..self.cols)
+                    .map(|col| self.visible(col, row) as char)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn visible(&self, col: u8, row: u8) -> u8 {
+        match self.controllers.len() {
+            1 => self.controllers[0].visible(self.cols, col, row),
+            _ => self.controllers[row as usize / 2].visible(self.cols, col, row % 2),
+        }
+    }
+
+    fn register(&self) -> Register {
+        if self.rs {
+            Register::Data
+        } else {
+            Register::Instruction
+        }
+    }
+
+    fn start_read(&mut self) {
+        if self.mode == Mode::Bits4 && self.read_low_nibble {
+            self.output <<= 4;
+            self.read_low_nibble = false;
+            return;
+        }
+
+        self.output = match self.register() {
+            Register::Instruction => self.controllers[self.selected].status(),
+            Register::Data => self.controllers[self.selected].read(),
+        };
+        self.read_low_nibble = self.mode == Mode::Bits4;
+    }
+}
+
+impl Bus for SimBus {
+    fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    fn set_rs(&mut self, register: Register) -> Result<(), Error> {
+        self.rs = register == Register::Data;
+        Ok(())
+    }
+
+    fn set_enable(&mut self, high: bool) -> Result<(), Error> {
+        if high == self.enable {
+            return Ok(());
+        }
+        self.enable = high;
+
+        if self.rw {
+            if high {
+                self.start_read();
+            }
+        } else if !high {
+            let register = self.register();
+            let controller = &mut self.controllers[self.selected];
+            match self.decoders[self.selected].latch(register, self.data) {
+                Some(Transfer::Instruction(value)) => controller.instruction(value),
+                Some(Transfer::Data(value)) => controller.data(value),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn write_nibble(&mut self, nibble: u8) -> Result<(), Error> {
+        self.data = (self.data & 0x0f) | (nibble << 4);
+        Ok(())
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.data = byte;
+        Ok(())
+    }
+
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    fn set_rw(&mut self, read: bool) -> Result<(), Error> {
+        self.rw = read;
+        Ok(())
+    }
+
+    fn read_data(&mut self) -> Result<u8, Error> {
+        Ok(match self.mode {
+            Mode::Bits8 => self.output,
+            Mode::Bits4 => self.output >> 4,
+        })
+    }
+
+    fn controllers(&self) -> u8 {
+        self.controllers.len() as u8
+    }
+
+    fn select_controller(&mut self, index: u8) -> Result<(), Error> {
+        if index as usize >= self.controllers.len() {
+            return Err(Error::Unsupported("second controller"));
+        }
+        self.selected = index as usize;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::NoDelay;
+    use crate::{glyphs, CharSize, Geometry, Wrap, LCD};
+
+    fn lcd(mode: Mode, cols: u8, rows: u8) -> LCD<SimBus, NoDelay> {
+        let mut lcd = LCD::with_bus_and_delay(SimBus::new(mode, cols, rows), NoDelay);
+        lcd.begin(cols, rows, CharSize::Dots5x8).unwrap();
+        lcd
+    }
+
+    #[test]
+    fn begin_configures_4_bit_interface() {
+        let lcd = lcd(Mode::Bits4, 16, 2);
+        let controller = lcd.bus().controller();
+
+        assert_eq!(controller.mode(), Mode::Bits4);
+        assert!(controller.is_two_lines());
+        assert!(!controller.is_font_5x10());
+        assert!(controller.is_display_on());
+        assert!(!controller.is_cursor_on());
+        assert!(controller.is_increment());
+        assert!(!controller.is_entry_shift());
+        assert_eq!(controller.address_counter(), 0);
+    }
+
+    #[test]
+    fn begin_configures_8_bit_interface() {
+        let mut lcd = lcd(Mode::Bits8, 16, 1);
+        lcd.print("8 bits").unwrap();
+
+        assert_eq!(lcd.bus().controller().mode(), Mode::Bits8);
+        assert_eq!(lcd.bus().screen(), vec!["8 bits          "]);
+    }
+
+    #[test]
+    fn set_cursor_addresses_all_rows() {
+        for &(cols, rows) in &[(16, 2), (16, 4), (20, 4)] {
+            let mut lcd = lcd(Mode::Bits4, cols, rows);
+            for row in 0..rows {
+                lcd.set_cursor(row, row).unwrap();
+                lcd.print(&format!("row{}", row)).unwrap();
+            }
+
+            let screen = lcd.bus().screen();
+            for row in 0..rows {
+                let expected = format!("{}row{}", " ".repeat(row as usize), row);
+                assert!(screen[row as usize].starts_with(&expected), "{}x{}: {:?}", cols, rows, screen);
+            }
+        }
+    }
+
+    #[test]
+    fn dual_controller_display_is_one_surface() {
+        let mut lcd = LCD::with_bus_and_delay(SimBus::dual(Mode::Bits4, 40), NoDelay);
+        lcd.begin(40, 4, CharSize::Dots5x8).unwrap();
+        for row in 0..4 {
+            lcd.set_cursor(row, row).unwrap();
+            lcd.print(&format!("row{}", row)).unwrap();
+        }
+
+        let screen = lcd.bus().screen();
+        for row in 0..4 {
+            let expected = format!("{}row{}", " ".repeat(row as usize), row);
+            assert!(screen[row as usize].starts_with(&expected), "{:?}", screen);
+        }
+        assert_eq!(lcd.read_char_at(3, 3).unwrap(), b'r');
+        assert_eq!(lcd.bus().controller_at(1).address_counter(), 0x47);
+
+        lcd.cursor().unwrap();
+        assert!(!lcd.bus().controller_at(0).is_cursor_on());
+        assert!(lcd.bus().controller_at(1).is_cursor_on());
+        lcd.set_cursor(0, 1).unwrap();
+        assert!(lcd.bus().controller_at(0).is_cursor_on());
+        assert!(!lcd.bus().controller_at(1).is_cursor_on());
+
+        lcd.clear().unwrap();
+        assert!(lcd.bus().screen().iter().all(|row| row.trim().is_empty()));
+    }
+
+    #[test]
+    fn char_wrap_continues_on_next_row() {
+        let mut lcd = lcd(Mode::Bits4, 8, 2);
+        lcd.set_wrap(Wrap::Char);
+        lcd.print("0123456789\nab").unwrap();
+        assert_eq!(lcd.bus().screen(), vec!["ab234567", "89      "]);
+        assert_eq!(lcd.cursor_position(), Some((2, 0)));
+
+        lcd.clear().unwrap();
+        lcd.set_cursor(7, 0).unwrap();
+        lcd.right_to_left().unwrap();
+        lcd.print("abcdefghi").unwrap();
+        assert_eq!(lcd.bus().screen(), vec!["hgfedcba", "       i"]);
+    }
+
+    #[test]
+    fn char_wrap_crosses_split_rows() {
+        // 16x1 "type 1" panel, whose halves are the two lines of an 8x2 controller
+        let mut lcd = LCD::with_bus_and_delay(SimBus::new(Mode::Bits4, 8, 2), NoDelay);
+        lcd.begin_with_geometry(Geometry::LCD16X1_TYPE1, CharSize::Dots5x8).unwrap();
+        lcd.set_wrap(Wrap::Char);
+        lcd.set_cursor(6, 0).unwrap();
+        lcd.print("abcdefghijklmnopqr").unwrap();
+        assert_eq!(lcd.bus().screen(), vec!["klmnopqr", "cdefghij"]);
+        assert_eq!(lcd.cursor_position(), Some((8, 0)));
+
+        lcd.clear().unwrap();
+        lcd.set_cursor(9, 0).unwrap();
+        lcd.right_to_left().unwrap();
+        lcd.print("abc").unwrap();
+        assert_eq!(lcd.bus().screen(), vec!["       c", "ba      "]);
+        assert_eq!(lcd.cursor_position(), Some((6, 0)));
+    }
+
+    #[test]
+    fn word_wrap_keeps_words_together() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        lcd.set_wrap(Wrap::Word);
+        lcd.print("The quick brown fox jumps").unwrap();
+        assert_eq!(lcd.bus().screen(), vec!["The quick brown ", "fox jumps       "]);
+
+        lcd.clear().unwrap();
+        lcd.print("0123456789abcdef ghijklmnopqrstuvwxyz").unwrap();
+        assert_eq!(lcd.bus().screen(), vec!["wxyz456789abcdef", "ghijklmnopqrstuv"]);
+    }
+
+    #[test]
+    fn buffered_frame_is_shown_on_flush() {
+        let mut lcd = LCD::with_bus_and_delay(SimBus::dual(Mode::Bits4, 40), NoDelay);
+        lcd.begin(40, 4, CharSize::Dots5x8).unwrap();
+        lcd.set_buffered(true).unwrap();
+        lcd.set_wrap(Wrap::Char);
+        lcd.set_cursor(36, 1).unwrap();
+        lcd.print("wrapped").unwrap();
+        lcd.autoscroll().unwrap();
+        assert!(lcd.bus().screen().iter().all(|row| row.trim().is_empty()));
+
+        lcd.flush().unwrap();
+        let screen = lcd.bus().screen();
+        assert!(screen[1].ends_with("wrap"), "{:?}", screen);
+        assert!(screen[2].starts_with("ped "), "{:?}", screen);
+        assert!(lcd.bus().controller_at(1).is_entry_shift());
+        assert_eq!(lcd.bus().controller_at(1).address_counter(), 3);
+    }
+
+    #[test]
+    fn glyphs_are_loaded_into_custom_characters() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        lcd.set_glyphs(Some(glyphs::builtin));
+        lcd.print("łą ł").unwrap();
+
+        let controller = lcd.bus().controller();
+        let codes: Vec<u8> = (0..4).map(|address| controller.ddram(address)).collect();
+        assert_eq!(codes, [0, 1, b' ', 0]);
+        assert_eq!(controller.cgram()[..8], glyphs::builtin('ł').unwrap());
+        assert_eq!(controller.cgram()[8..16], glyphs::builtin('ą').unwrap());
+        assert!(controller.cgram()[16..].iter().all(|row| *row == 0));
+
+        lcd.set_cursor(0, 1).unwrap();
+        lcd.print("C:\\~").unwrap();
+        let controller = lcd.bus().controller();
+        let codes: Vec<u8> = (0x40..0x44).map(|address| controller.ddram(address)).collect();
+        assert_eq!(codes, [b'C', b':', 2, 3]);
+        assert_eq!(controller.cgram()[16..24], glyphs::builtin('\\').unwrap());
+    }
+
+    #[test]
+    fn glyphs_reuse_slots_not_on_screen() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        let smiley = [0x00, 0x0a, 0x00, 0x00, 0x11, 0x0e, 0x00, 0x00];
+        lcd.create_char(0, smiley).unwrap();
+        lcd.set_glyphs(Some(glyphs::builtin));
+
+        // 7 slots are left for the glyphs, the 8th one falls back to the transliteration
+        lcd.print("ąćęłńóśź").unwrap();
+        assert_eq!(lcd.read_screen().unwrap()[0][..8], *b"\x01\x02\x03\x04\x05\x06\x07z");
+
+        // the least recently used slot is reused once its glyph is gone
+        lcd.clear().unwrap();
+        lcd.print("ść").unwrap();
+        lcd.set_cursor(0, 1).unwrap();
+        lcd.print("ź").unwrap();
+        let controller = lcd.bus().controller();
+        assert_eq!(controller.ddram(0x40), 1);
+        assert_eq!(controller.cgram()[..8], smiley);
+        assert_eq!(controller.cgram()[8..16], glyphs::builtin('ź').unwrap());
+    }
+
+    #[test]
+    fn named_glyphs_are_word_wrapped_as_one_character() {
+        let mut lcd = lcd(Mode::Bits4, 8, 2);
+        lcd.register_glyph("heart", [0x00, 0x0a, 0x1f, 0x1f, 0x0e, 0x04, 0x00, 0x00]).unwrap();
+        lcd.set_wrap(Wrap::Word);
+        lcd.print("I {heart} Rust!{heart}").unwrap();
+
+        assert_eq!(lcd.bus().screen(), vec!["I \u{0}     ", "Rust!\u{0}  "]);
+    }
+
+    #[test]
+    fn glyphs_keep_slots_shifted_into_view() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        lcd.set_glyphs(Some(glyphs::builtin));
+        lcd.print("0123456789abcdefą").unwrap();
+        lcd.scroll_display_left().unwrap();
+        assert_eq!(lcd.bus().screen()[0], "123456789abcdef\0");
+
+        lcd.set_cursor(0, 1).unwrap();
+        lcd.print("ćęłńóśźż").unwrap();
+        let controller = lcd.bus().controller();
+        assert_eq!(controller.cgram()[..8], glyphs::builtin('ą').unwrap());
+        assert_eq!(controller.ddram(0x47), b'z');
+
+        lcd.clear().unwrap();
+        lcd.print("ż").unwrap();
+        assert_eq!(lcd.bus().controller().ddram(0x00), 0);
+    }
+
+    #[test]
+    fn font_5x10_has_4_custom_characters() {
+        let mut lcd = LCD::with_bus_and_delay(SimBus::new(Mode::Bits4, 16, 1), NoDelay);
+        lcd.begin(16, 1, CharSize::Dots5x10).unwrap();
+        let tall = [0x1f, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1f];
+        lcd.create_glyph(1, tall).unwrap();
+        assert_eq!(lcd.bus().controller().cgram()[16..27], tall);
+        assert_eq!(lcd.read_cgram(1).unwrap(), tall.into());
+        assert!(matches!(lcd.create_glyph(4, tall), Err(Error::InvalidGlyph(_))));
+        assert!(matches!(lcd.create_char(0, [0x1f; 8]), Err(Error::InvalidGlyph(_))));
+        assert!(matches!(lcd.read_cgram(4), Err(Error::InvalidGlyph(_))));
+
+        // slots 0, 2 and 3 are left for the named glyphs, shown by even codes
+        for (i, name) in ["a", "b", "c", "d"].iter().enumerate() {
+            lcd.register_glyph(name, [i as u8; 11]).unwrap();
+        }
+        lcd.register_glyph("small", [0x1f; 8]).unwrap();
+        lcd.print("{a}{b}{c}{d}{small}").unwrap();
+        let codes: Vec<u8> = (0..5).map(|address| lcd.bus().controller().ddram(address)).collect();
+        assert_eq!(codes, [0, 4, 6, b'?', b'?']);
+        assert_eq!(lcd.bus().controller().cgram()[48..59], [2; 11]);
+    }
+
+    #[test]
+    fn scroll_display_moves_visible_window() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        lcd.print("abc").unwrap();
+
+        lcd.scroll_display_right().unwrap();
+        assert_eq!(lcd.bus().screen()[0], " abc            ");
+
+        lcd.scroll_display_left().unwrap();
+        lcd.scroll_display_left().unwrap();
+        assert_eq!(lcd.bus().screen()[0], "bc              ");
+    }
+
+    #[test]
+    fn right_to_left_writes_backwards() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        lcd.set_cursor(15, 0).unwrap();
+        lcd.right_to_left().unwrap();
+        lcd.print("abc").unwrap();
+
+        assert_eq!(lcd.bus().screen()[0], "             cba");
+    }
+
+    #[test]
+    fn autoscroll_keeps_cursor_in_place() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        lcd.set_cursor(10, 0).unwrap();
+        lcd.autoscroll().unwrap();
+        lcd.print("abcd").unwrap();
+
+        assert_eq!(lcd.bus().screen()[0], "      abcd      ");
+        assert_eq!(lcd.bus().controller().display_shift(), 4);
+    }
+
+    #[test]
+    fn create_char_fills_cgram() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        let glyph = [0x00, 0x0e, 0x1f, 0x1f, 0x1f, 0x0e, 0x00, 0x00];
+        lcd.create_char(2, glyph).unwrap();
+
+        assert_eq!(&lcd.bus().controller().cgram()[16..24], &glyph);
+    }
+
+    #[test]
+    fn address_counter_wraps_between_lines() {
+        let mut controller = Hd44780::new();
+        controller.instruction(0x38);
+        controller.instruction(0x80 | 0x27);
+        controller.data(b'x');
+
+        assert_eq!(controller.address_counter(), 0x40);
+        assert_eq!(controller.ddram(0x27), b'x');
+    }
+
+    #[test]
+    fn reads_back_through_the_bus() {
+        let mut bus = SimBus::new(Mode::Bits8, 16, 2);
+        bus.controllers[0].instruction(0x38);
+        bus.controllers[0].instruction(0xc5);
+        bus.set_rw(true).unwrap();
+        bus.set_enable(true).unwrap();
+
+        assert_eq!(bus.read_data().unwrap(), 0x45);
+    }
+
+    #[test]
+    fn lcd_reads_back_display_memory() {
+        for mode in &[Mode::Bits4, Mode::Bits8] {
+            let mut lcd = lcd(*mode, 16, 2);
+            lcd.print("Hello").unwrap();
+            lcd.set_cursor(3, 1).unwrap();
+            lcd.print("LCD").unwrap();
+
+            assert_eq!(lcd.address_counter().unwrap(), 0x46);
+            assert_eq!(lcd.read_char_at(1, 0).unwrap(), b'e');
+            assert_eq!(lcd.address_counter().unwrap(), 0x46);
+
+            let screen = lcd.read_screen().unwrap();
+            assert_eq!(&screen[0][..], &b"Hello           "[..]);
+            assert_eq!(&screen[1][..], &b"   LCD          "[..]);
+
+            lcd.print("!").unwrap();
+            assert_eq!(lcd.bus().screen()[1], "   LCD!         ");
+        }
+    }
+
+    #[test]
+    fn lcd_reads_back_in_right_to_left_mode() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        let glyph = [0x00, 0x0e, 0x1f, 0x1f, 0x1f, 0x0e, 0x00, 0x00];
+        lcd.create_char(2, glyph).unwrap();
+        lcd.print("Hello").unwrap();
+        lcd.set_cursor(5, 1).unwrap();
+        lcd.right_to_left().unwrap();
+        lcd.print("DCL").unwrap();
+
+        let screen = lcd.read_screen().unwrap();
+        assert_eq!(&screen[0][..], &b"Hello           "[..]);
+        assert_eq!(&screen[1][..], &b"   LCD          "[..]);
+        assert_eq!(lcd.read_cgram(2).unwrap(), glyph.into());
+
+        // the entry mode and the cursor are restored
+        assert!(!lcd.bus().controller().is_increment());
+        lcd.print("<").unwrap();
+        assert_eq!(lcd.bus().screen()[1], "  <LCD          ");
+    }
+
+    #[test]
+    fn lcd_reads_back_cgram() {
+        let mut lcd = lcd(Mode::Bits4, 16, 2);
+        let glyph = [0x00, 0x0e, 0x1f, 0x1f, 0x1f, 0x0e, 0x00, 0x00];
+        lcd.create_char(5, glyph).unwrap();
+
+        assert_eq!(lcd.read_cgram(5).unwrap(), glyph.into());
+        assert_eq!(lcd.read_cgram(4).unwrap(), [0; 8].into());
+        assert!(matches!(lcd.read_cgram(8), Err(Error::InvalidGlyph(_))));
+    }
+}
@@ -0,0 +1,78 @@
+/// Timing of the HD44780 protocol, in microseconds
+///
+/// The execution times are only waited for when the busy flag cannot be read, i.e. when RW is
+/// not wired. Use one of the presets or adjust them for the display at hand:
+///
+/// ```rust
+/// use rpi_lcd::Timing;
+///
+/// let timing = Timing {
+///     power_on_delay: 500_000,
+///     ..Timing::HD44780
+/// };
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Timing {
+    /// Time ENABLE is held high and low when latching a transfer
+    pub enable_pulse_width: u32,
+
+    /// Execution time of instructions and data writes, except clear display and return home
+    pub execution_time: u32,
+
+    /// Execution time of clear display and return home
+    pub clear_home_time: u32,
+
+    /// Time to wait in [begin()](struct.LCD.html#method.begin) before the first instruction,
+    /// for the supply voltage to settle after power on
+    pub power_on_delay: u32,
+
+    /// Time to wait after the first function set of the reset sequence (datasheet: more than
+    /// 4.1 ms). The reset sequence is timed even when the busy flag can be read, and all the
+    /// presets keep the datasheet minimum with a margin.
+    pub reset_delay: u32,
+
+    /// Time to wait after the second function set of the reset sequence (datasheet: more than
+    /// 100 µs)
+    pub reset_short_delay: u32,
+}
+
+impl Timing {
+
+    /// Hitachi HD44780 clocked at 270 kHz (37 µs execution time, 1.52 ms clear/home), with a
+    /// margin for the oscillator tolerance. This is the default.
+    pub const HD44780: Timing = Timing {
+        enable_pulse_width: 1,
+        execution_time: 50,
+        clear_home_time: 2000,
+        power_on_delay: 50_000,
+        reset_delay: 4500,
+        reset_short_delay: 150,
+    };
+
+    /// Faster compatible controllers (e.g. ST7066U or SPLC780D at their nominal clock)
+    pub const FAST: Timing = Timing {
+        enable_pulse_width: 1,
+        execution_time: 40,
+        clear_home_time: 1600,
+        power_on_delay: 40_000,
+        reset_delay: 4500,
+        reset_short_delay: 150,
+    };
+
+    /// Displays driven over long cables or at low supply voltage, where edges are slow and the
+    /// controller oscillator runs below its nominal frequency
+    pub const SLOW: Timing = Timing {
+        enable_pulse_width: 10,
+        execution_time: 120,
+        clear_home_time: 5000,
+        power_on_delay: 100_000,
+        reset_delay: 4500,
+        reset_short_delay: 150,
+    };
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing::HD44780
+    }
+}